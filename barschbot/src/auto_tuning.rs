@@ -1,12 +1,11 @@
-use std::time::Duration;
+use std::{sync::Mutex, time::Duration};
 
 use num_bigint::BigInt;
 use num_traits::{Zero, One, ToPrimitive};
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::{bb_settings::{self, BBSettings, FactorName}, endgame_table::EndgameTable, game::{Game, GameState}, karpfen_bot::KarpfenBot, kb_settings::{self, KBSettings}, match_handler::{self, barsch_vs_karpfen, barsch_vs_sf, play_bot_game}, opening_book::OpeningBook};
-
-const THREAD_COUNT: usize = 14;
+use barschbot::{bb_settings::{self, BBSettings, FactorName}, endgame_table::EndgameTable, game::Game, game_result::GameResult, karpfen_bot::KarpfenBot, kb_settings::{self, KBSettings}, opening_book::OpeningBook, pgn::{self, PgnGame}};
+use crate::match_handler::{self, barsch_vs_karpfen, barsch_vs_sf, play_bot_game};
 
 pub fn print_confidence(wins: i32, losses: i32, draws: i32) -> f64 {
     let sum = wins + losses + draws;
@@ -67,12 +66,12 @@ pub fn print_confidence(wins: i32, losses: i32, draws: i32) -> f64 {
     }
 }
 
-fn auto_tune(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, mut start_settings: BBSettings) {
+pub fn auto_tune(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, mut start_settings: BBSettings, stockfish_path: &str, thread_count: usize) {
     let mut it: usize = FactorName::SafeMobilityP as usize;
     loop {
         let f = bb_settings::ALL_NAMES[it % bb_settings::ALL_NAMES.len()];
         let init = start_settings.eval_factors.get_value(f);
-        let better = optimize_value_self_play(fens,  book, table, f, &start_settings.clone());
+        let better = optimize_value_self_play(fens,  book, table, f, &start_settings.clone(), stockfish_path, thread_count);
 
         start_settings.eval_factors.set_value(f, better);
 
@@ -86,7 +85,7 @@ fn auto_tune(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, mut
     }
 }
 
-fn optimize_value_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings) -> f32 {
+fn optimize_value_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings, stockfish_path: &str, thread_count: usize) -> f32 {
     let mut best_settings = start_settings.clone();
 
     //start_settings.eval_factors.print_all();
@@ -98,7 +97,7 @@ fn optimize_value_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &End
         it += 1;
 
         //let (val, sup) = test_eval_range_self_play(table, factor_name, &best_settings);
-        let (val, sup) = test_eval_range_stock_fish(fens, book, table, factor_name, &best_settings);
+        let (val, sup) = test_eval_range_stock_fish(fens, book, table, factor_name, &best_settings, stockfish_path, thread_count);
         
         //if sup < 0.60 {
         //    break;
@@ -112,7 +111,7 @@ fn optimize_value_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &End
     return best_settings.eval_factors.get_value(factor_name);
 }
 
-fn test_eval_range_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings) -> (f32, f64) {
+fn test_eval_range_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings, stockfish_path: &str, thread_count: usize) -> (f32, f64) {
     
     const STEP_COUNT: i32 = 5;
     const RANGE_DIV: f32 = 0.1;
@@ -139,7 +138,7 @@ fn test_eval_range_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &En
         
         improv.eval_factors.set_value(factor_name, val);
 
-        let (wins, losses, draws) = play_sf_parallel(fens, book, table, &improv);
+        let (wins, losses, draws) = play_sf_parallel(fens, book, table, &improv, stockfish_path, thread_count);
 
         results.push((val, print_confidence(wins, losses, draws)));
 
@@ -161,7 +160,7 @@ fn test_eval_range_self_play(fens: &&Vec<String>, book: &OpeningBook, table: &En
     return results[0];
 }
 
-fn test_eval_range_stock_fish(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings) -> (f32, f64) {
+fn test_eval_range_stock_fish(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, factor_name: bb_settings::FactorName, start_settings: &BBSettings, stockfish_path: &str, thread_count: usize) -> (f32, f64) {
     
     const STEP_COUNT: i32 = 4;
     const RANGE_DIV: f32 = 0.1;
@@ -188,7 +187,7 @@ fn test_eval_range_stock_fish(fens: &&Vec<String>, book: &OpeningBook, table: &E
         
         improv.eval_factors.set_value(factor_name, val);
 
-        let (wins, losses, draws) = play_sf_parallel(fens, book, table, &improv);
+        let (wins, losses, draws) = play_sf_parallel(fens, book, table, &improv, stockfish_path, thread_count);
 
         results.push((val, print_confidence(wins, losses, draws)));
 
@@ -210,13 +209,13 @@ fn test_eval_range_stock_fish(fens: &&Vec<String>, book: &OpeningBook, table: &E
     return results[0];
 }
 
-fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, settings: &BBSettings) -> (i32, i32, i32) {
+fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTable, settings: &BBSettings, stockfish_path: &str, thread_count: usize) -> (i32, i32, i32) {
     let mut threads = Vec::new();
-    let fens_per_thread = fens.len() / THREAD_COUNT;
-    let mut reisdue = fens.len() % THREAD_COUNT;
+    let fens_per_thread = fens.len() / thread_count;
+    let mut reisdue = fens.len() % thread_count;
 
     let mut fen_index = 0;
-    for t in 0..THREAD_COUNT {
+    for t in 0..thread_count {
         let mut list = Vec::new();
         for i in 0..fens_per_thread {
             list.push(fen_index);
@@ -232,14 +231,14 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
         threads.push(list);
     }
     
-    threads.par_iter_mut().for_each(|list| {
+    let results = threads.par_iter().map(|list| {
         let mut barsch_wins = 0;
         let mut sf_wins = 0;
         let mut draws = 0;
         let mut barsch_duration = Duration::ZERO;
         let mut sf_duration = Duration::ZERO;
 
-        let mut cmd = match_handler::get_stock_fish_process();
+        let mut cmd = match_handler::get_stock_fish_process(stockfish_path);
 
         let mut count = 0;
         for i in 0..list.len() {
@@ -254,11 +253,11 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
                 draws += 1;
             }
             else {
-                if white_start == (res == GameState::WhiteCheckmate) {
-                    sf_wins += 1;
+                if res.is_win_for(white_start) {
+                    barsch_wins += 1;
                 }
                 else {
-                    barsch_wins += 1;
+                    sf_wins += 1;
                 }
             }
             
@@ -271,11 +270,11 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
                 draws += 1;
             }
             else {
-                if white_start != (res == GameState::WhiteCheckmate) {
-                    sf_wins += 1;
+                if res.is_win_for(!white_start) {
+                    barsch_wins += 1;
                 }
                 else {
-                    barsch_wins += 1;
+                    sf_wins += 1;
                 }
             }
 
@@ -286,12 +285,8 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
         }
         
         //println!("Chunk done Sum: W {} L {} D {}", a_wins, b_wins, draws);
-        list[0] = barsch_wins;
-        list[1] = sf_wins;
-        list[2] = draws;
-        list[3] = barsch_duration.as_millis() as usize;
-        list[4] = sf_duration.as_millis() as usize;
-    });
+        return [barsch_wins, sf_wins, draws, barsch_duration.as_millis() as usize, sf_duration.as_millis() as usize];
+    }).collect::<Vec<_>>();
 
     let mut sum_a = 0;
     let mut sum_b = 0;
@@ -299,7 +294,7 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
     let mut sum_dur_a = 0;
     let mut sum_dur_b = 0;
 
-    for list in threads {
+    for list in results {
         sum_a += list[0];
         sum_b += list[1];
         sum_d += list[2];
@@ -312,13 +307,33 @@ fn play_sf_parallel(fens: &&Vec<String>, book: &OpeningBook, table: &EndgameTabl
     return (sum_a as i32, sum_b as i32, sum_d as i32);
 }
 
-pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_table: &EndgameTable, bb_setting: &BBSettings, kb_settings: &KBSettings) -> (i32, i32, i32) {
+//Every finished game is appended to pgn_path if given
+pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_table: &EndgameTable, bb_setting: &BBSettings, kb_settings: &KBSettings, thread_count: usize, pgn_path: Option<&str>) -> (i32, i32, i32) {
+    let pgn_lock = Mutex::new(());
+
+    let archive = |game: Game, result: &GameResult, comments: Vec<String>, barsch_white: bool| {
+        if let Some(path) = pgn_path {
+            //The runner may end the game early (illegal move), so the result is not always readable from the board
+            let mut pgn = PgnGame::new(game);
+            pgn.set_result(result);
+            pgn.comments = comments;
+            pgn.set_tag("Event", "compare_fish");
+            pgn.set_tag("White", if barsch_white { "BarschBot" } else { "KarpfenBot" });
+            pgn.set_tag("Black", if barsch_white { "KarpfenBot" } else { "BarschBot" });
+
+            let _guard = pgn_lock.lock().unwrap();
+            if let Err(e) = pgn::append_to_file(path, &pgn) {
+                println!("Could not write {}: {}", path, e);
+            }
+        }
+    };
+
     let mut threads = Vec::new();
-    let fens_per_thread = fens.len() / THREAD_COUNT;
-    let mut reisdue = fens.len() % THREAD_COUNT;
+    let fens_per_thread = fens.len() / thread_count;
+    let mut reisdue = fens.len() % thread_count;
 
     let mut fen_index = 0;
-    for t in 0..THREAD_COUNT {
+    for t in 0..thread_count {
         let mut list = Vec::new();
         for i in 0..fens_per_thread {
             list.push(fen_index);
@@ -334,15 +349,13 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
         threads.push(list);
     }
     
-    threads.par_iter_mut().for_each(|list| {
+    let results = threads.par_iter().map(|list| {
         let mut barsch_wins = 0;
         let mut sf_wins = 0;
         let mut draws = 0;
         let mut barsch_duration = Duration::ZERO;
         let mut karpfen_duration = Duration::ZERO;
 
-        let mut cmd = match_handler::get_stock_fish_process();
-
         let mut bot = KarpfenBot::with_settings(kb_settings.clone());
 
         let mut count = 0;
@@ -351,8 +364,11 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
             let white_start = Game::from_fen(&fen).is_whites_turn();
             
             bot.reset();
+            let mut game = Game::from_fen(&fen);
+            let mut comments = Vec::new();
             let (res, dur_a, dur_b) = barsch_vs_karpfen(
-                &mut Game::from_fen(&fen), opening_book, endgame_table, &bb_setting, &mut bot, true);       
+                &mut game, opening_book, endgame_table, &bb_setting, &mut bot, true, &mut comments);       
+            archive(game, &res, comments, white_start);
             barsch_duration += dur_a;
             karpfen_duration += dur_b;
 
@@ -360,17 +376,20 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
                 draws += 1;
             }
             else {
-                if white_start == (res == GameState::WhiteCheckmate) {
-                    sf_wins += 1;
+                if res.is_win_for(white_start) {
+                    barsch_wins += 1;
                 }
                 else {
-                    barsch_wins += 1;
+                    sf_wins += 1;
                 }
             }
             
             bot.reset();
+            let mut game = Game::from_fen(&fen);
+            let mut comments = Vec::new();
             let (res, dur_a, dur_b) = barsch_vs_karpfen(
-                &mut Game::from_fen(&fen), opening_book, endgame_table, &bb_setting, &mut bot, false);       
+                &mut game, opening_book, endgame_table, &bb_setting, &mut bot, false, &mut comments);       
+            archive(game, &res, comments, !white_start);
 
             barsch_duration += dur_a;
             karpfen_duration += dur_b;
@@ -379,11 +398,11 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
                 draws += 1;
             }
             else {
-                if white_start != (res == GameState::WhiteCheckmate) {
-                    sf_wins += 1;
+                if res.is_win_for(!white_start) {
+                    barsch_wins += 1;
                 }
                 else {
-                    barsch_wins += 1;
+                    sf_wins += 1;
                 }
             }
 
@@ -394,12 +413,8 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
         }
         
         //println!("Chunk done Sum: W {} L {} D {}", a_wins, b_wins, draws);
-        list[0] = barsch_wins;
-        list[1] = sf_wins;
-        list[2] = draws;
-        list[3] = barsch_duration.as_millis() as usize;
-        list[4] = karpfen_duration.as_millis() as usize;
-    });
+        return [barsch_wins, sf_wins, draws, barsch_duration.as_millis() as usize, karpfen_duration.as_millis() as usize];
+    }).collect::<Vec<_>>();
 
     let mut sum_a = 0;
     let mut sum_b = 0;
@@ -407,7 +422,7 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
     let mut sum_dur_a = 0;
     let mut sum_dur_b = 0;
 
-    for list in threads {
+    for list in results {
         sum_a += list[0];
         sum_b += list[1];
         sum_d += list[2];
@@ -420,13 +435,13 @@ pub fn compare_fish(fens: &Vec<String>, opening_book: &OpeningBook, endgame_tabl
     return (sum_a as i32, sum_b as i32, sum_d as i32);
 }
 
-pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table: &EndgameTable, a: &BBSettings, b: &BBSettings) -> (i32, i32, i32) {
+pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table: &EndgameTable, a: &BBSettings, b: &BBSettings, thread_count: usize) -> (i32, i32, i32) {
     let mut threads = Vec::new();
-    let fens_per_thread = fens.len() / THREAD_COUNT;
-    let mut reisdue = fens.len() % THREAD_COUNT;
+    let fens_per_thread = fens.len() / thread_count;
+    let mut reisdue = fens.len() % thread_count;
 
     let mut fen_index = 0;
-    for t in 0..THREAD_COUNT {
+    for t in 0..thread_count {
         let mut list = Vec::new();
         for i in 0..fens_per_thread {
             list.push(fen_index);
@@ -442,7 +457,7 @@ pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table:
         threads.push(list);
     }
     
-    threads.par_iter_mut().for_each(|list| {
+    let results = threads.par_iter().map(|list| {
         let mut a_wins = 0;
         let mut b_wins = 0;
         let mut draws = 0;
@@ -462,11 +477,11 @@ pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table:
                 draws += 1;
             }
             else {
-                if white_start == (res == GameState::WhiteCheckmate) {
-                    b_wins += 1;
+                if res.is_win_for(white_start) {
+                    a_wins += 1;
                 }
                 else {
-                    a_wins += 1;
+                    b_wins += 1;
                 }
             }
             
@@ -478,11 +493,11 @@ pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table:
                 draws += 1;
             }
             else {
-                if white_start != (res == GameState::WhiteCheckmate) {
-                    b_wins += 1;
+                if res.is_win_for(!white_start) {
+                    a_wins += 1;
                 }
                 else {
-                    a_wins += 1;
+                    b_wins += 1;
                 }
             }
 
@@ -493,12 +508,8 @@ pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table:
         }
         
         //println!("Chunk done Sum: W {} L {} D {}", a_wins, b_wins, draws);
-        list[0] = a_wins;
-        list[1] = b_wins;
-        list[2] = draws;
-        list[3] = duration_a.as_millis() as usize;
-        list[4] = duration_b.as_millis() as usize;
-    });
+        return [a_wins, b_wins, draws, duration_a.as_millis() as usize, duration_b.as_millis() as usize];
+    }).collect::<Vec<_>>();
 
     let mut sum_a = 0;
     let mut sum_b = 0;
@@ -506,7 +517,7 @@ pub fn compare_settings_parallel(fens: &Vec<String>, book: &OpeningBook, table:
     let mut sum_dur_a = 0;
     let mut sum_dur_b = 0;
 
-    for list in threads {
+    for list in results {
         sum_a += list[0];
         sum_b += list[1];
         sum_d += list[2];
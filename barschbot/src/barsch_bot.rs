@@ -5,7 +5,7 @@ use num::complex::ComplexFloat;
 use rand::seq::SliceRandom;
 
 use crate::{game::{Game, GameState}, chess_move::{ChessMove, self, NULL_MOVE}, piece_type::PieceType, bit_board::{BitBoard, self}, 
-    evaluation::*, endgame_table::{self, EndgameTable, UNDEFINED, BoardState}, bb_settings::{self, BBSettings}, opening_book::OpeningBook, bitboard_helper};
+    evaluation::*, endgame_table::{self, EndgameTable, UNDEFINED, BoardState}, bb_settings::{self, BBSettings}, opening_book::OpeningBook, bitboard_helper, search_info::{InfoScore, SearchInfo, SearchReporter, SilentReporter}, time_manager::{SearchLimits, TimeManager}};
 
 const MAX_VALUE: f32 =  f32::INFINITY;
 
@@ -27,6 +27,10 @@ impl Stats {
 }
 
 pub fn get_best_move(game: &mut Game, table: &EndgameTable, bb_settings: &BBSettings, book: &OpeningBook) -> ChessMove{
+    return get_best_move_with_limits(game, table, bb_settings, book, &SearchLimits::from_depth(bb_settings.max_depth, bb_settings.min_search_time), &mut SilentReporter);
+}
+
+pub fn get_best_move_with_limits(game: &mut Game, table: &EndgameTable, bb_settings: &BBSettings, book: &OpeningBook, limits: &SearchLimits, reporter: &mut dyn SearchReporter) -> ChessMove{
     //println!("Looking for best move");
     let om = book.get_move(game.get_board().get_zoberist_hash());
 
@@ -40,7 +44,7 @@ pub fn get_best_move(game: &mut Game, table: &EndgameTable, bb_settings: &BBSett
         return end_game_move(game, table);
     }
     
-    return iterative_deepening(game, table, bb_settings).0; 
+    return iterative_deepening_with_limits(game, table, bb_settings, limits, reporter).0; 
 }
 
 pub fn end_game_move(game: &mut Game, table: &EndgameTable) -> ChessMove {
@@ -119,6 +123,11 @@ pub fn get_relative_endgame_eval(board: &BitBoard, table: &EndgameTable) -> (f32
 }
 
 pub fn iterative_deepening(game: &mut Game, table: &EndgameTable, bb_settings: &BBSettings) -> (ChessMove, f32) {
+    return iterative_deepening_with_limits(game, table, bb_settings, &SearchLimits::from_depth(bb_settings.max_depth, bb_settings.min_search_time), &mut SilentReporter);
+}
+
+//Returns the result of the last iteration that was not aborted by the time manager
+pub fn iterative_deepening_with_limits(game: &mut Game, table: &EndgameTable, bb_settings: &BBSettings, limits: &SearchLimits, reporter: &mut dyn SearchReporter) -> (ChessMove, f32) {
     const PRINT: bool = false;
     
     let mut map = HashMap::new();
@@ -131,16 +140,40 @@ pub fn iterative_deepening(game: &mut Game, table: &EndgameTable, bb_settings: &
     let mut start = Instant::now();
     let mut pair: (ChessMove, f32, GameState) = (NULL_MOVE, 0.0, GameState::Undecided);
     let mut stats = Stats::new();
+    let mut time_manager = TimeManager::new(limits, game.is_whites_turn());
 
     let mut md = 1 as u8;
 
     let mut list = game.get_legal_moves();    
+    let root_depth = game.move_depth();
 
-    while md <= bb_settings.max_depth || start.elapsed().as_millis() < (bb_settings.min_search_time as u128) {
-        pair = alpha_beta_nega_max(game, -MAX_VALUE, MAX_VALUE,  md, md, bb_settings.max_extensions, table, &mut map, bb_settings, &mut stats);
+    loop {
+        let result = alpha_beta_nega_max(game, -MAX_VALUE, MAX_VALUE,  md, md, bb_settings.max_extensions, root_depth, table, &mut map, bb_settings, &mut stats, &mut time_manager);
         //pair = negation_max(game, i);
+
+        if time_manager.is_stopped() {
+            if pair.0.is_null_move() && list.len() > 0 {
+                pair.0 = list[0];
+            }
+            break;
+        }
+
+        pair = result;
     
         let duration = start.elapsed();
+        let pv = get_pv(game, &map, md as usize + bb_settings.max_extensions as usize);
+
+        reporter.report(&SearchInfo {
+            depth: md,
+            //Extensions are not tracked, so this is only the nominal depth
+            seldepth: md,
+            multipv: 1,
+            score: get_info_score(game, &pv, pair.1, pair.2),
+            nodes: stats.nodes,
+            time: duration.as_millis() as u64,
+            hashfull: None,
+            pv,
+        });
         
         if PRINT {
             println!("{:?}", duration);
@@ -157,7 +190,7 @@ pub fn iterative_deepening(game: &mut Game, table: &EndgameTable, bb_settings: &
             println!(" Move: {}", pair.0.get_uci());
         }
     
-        if pair.2.is_checkmate() {
+        if pair.2.is_checkmate() || !time_manager.should_start_iteration(md, None) {
             break;
         }
         md += 1;
@@ -170,6 +203,59 @@ pub fn iterative_deepening(game: &mut Game, table: &EndgameTable, bb_settings: &
     return (pair.0, pair.1);
 }
 
+//Mates only come as a GameState without a distance, so it is taken from a pv that ends in mate
+fn get_info_score(game: &mut Game, pv: &[ChessMove], score: f32, gs: GameState) -> InfoScore {
+    //Reported instead of a mate when the pv does not show the distance
+    const MATE_BOUND: i32 = 10_000;
+
+    if gs.is_checkmate() {
+        for m in pv {
+            game.make_move(*m);
+        }
+
+        let mated = game.get_game_state().is_checkmate();
+
+        for _ in 0..pv.len() {
+            game.undo_move();
+        }
+
+        let sign = if score > 0.0 { 1 } else { -1 };
+
+        if mated {
+            return InfoScore::Mate(sign * ((pv.len() as i32 + 1) / 2));
+        }
+
+        return InfoScore::Centipawns(sign * MATE_BOUND);
+    }
+
+    return InfoScore::Centipawns((score * 100.0).round() as i32);
+}
+
+//Follows the stored best moves from the current position
+fn get_pv(game: &mut Game, map: &HashMap<u64, (u8, ChessMove, f32, GameState)>, max_length: usize) -> Vec<ChessMove> {
+    let mut pv = Vec::new();
+
+    while pv.len() < max_length {
+        let m = match map.get(&game.get_board().get_zoberist_hash()) {
+            Some(entry) => entry.1,
+            None => break,
+        };
+
+        if m.is_null_move() || !game.get_legal_moves().contains(&m) {
+            break;
+        }
+
+        game.make_move(m);
+        pv.push(m);
+    }
+
+    for _ in 0..pv.len() {
+        game.undo_move();
+    }
+
+    return pv;
+}
+
 fn move_sorter(list: &mut ArrayVec<ChessMove, 200>, prev_best: ChessMove) {
     const PIECE_VALUES: [i32; 6] = [100, 280, 320, 500, 900, 100000];
 
@@ -236,7 +322,7 @@ pub fn better_move_sorter(list: &mut ArrayVec<ChessMove, 200>, board: &BitBoard,
         sum += board.get_piece_captures_at(cm.move_piece_type, cm.target_square).iter()
             .map(|x| PIECE_VALUES[*x as usize]).sum::<i32>();
 
-        //println!("Move: {} sum: {}", cm.get_board_name(&board), sum);
+        //println!("Move: {} sum: {}", cm.get_san(&board), sum);
 
         return -sum;
     });
@@ -277,8 +363,12 @@ pub fn best_move_sorter(list: &mut ArrayVec<ChessMove, 200>, game: &mut Game, ma
     //board.print_local_moves(&list);
 }
 
-pub fn alpha_beta_nega_max(game: &mut Game, mut alpha: f32, beta: f32, depth_left: u8, max_depth: u8, extensions_left: u8, table: &EndgameTable, map: &mut HashMap<u64, (u8, ChessMove, f32, GameState)>, settings: &BBSettings, stats: &mut Stats) -> (ChessMove, f32, GameState) {        
+pub fn alpha_beta_nega_max(game: &mut Game, mut alpha: f32, beta: f32, depth_left: u8, max_depth: u8, extensions_left: u8, root_depth: u32, table: &EndgameTable, map: &mut HashMap<u64, (u8, ChessMove, f32, GameState)>, settings: &BBSettings, stats: &mut Stats, time_manager: &mut TimeManager) -> (ChessMove, f32, GameState) {        
     stats.nodes += 1;
+
+    if time_manager.check(stats.nodes) {
+        return (NULL_MOVE, 0.0, GameState::Undecided);
+    }
     
     if depth_left == 0 {
         stats.qs += 1;
@@ -298,7 +388,12 @@ pub fn alpha_beta_nega_max(game: &mut Game, mut alpha: f32, beta: f32, depth_lef
         return (cm, eval, gs);
     }
 
-    if game.get_game_state() != GameState::Undecided {
+    let gs = game.get_search_state(root_depth, settings.twofold_repetition);
+    if gs == GameState::Repetition {
+        return (chess_move::NULL_MOVE, 0.0, gs);
+    }
+
+    if gs != GameState::Undecided {
         let pair = static_eval_float(game, &settings.eval_factors, false);
         return (chess_move::NULL_MOVE, pair.0, pair.1);
     }
@@ -339,9 +434,13 @@ pub fn alpha_beta_nega_max(game: &mut Game, mut alpha: f32, beta: f32, depth_lef
  
         let sub = if list.len() < 3 && extensions_left > 0 { 0 } else { 1 };
 
-        let (line, mut value, gs) = alpha_beta_nega_max(game,  -beta, -alpha, depth_left - sub, max_depth, extensions_left -  (1 - sub), table, map, settings, stats);
+        let (line, mut value, gs) = alpha_beta_nega_max(game,  -beta, -alpha, depth_left - sub, max_depth, extensions_left -  (1 - sub), root_depth, table, map, settings, stats, time_manager);
         
         game.undo_move();
+
+        if time_manager.is_stopped() {
+            return (NULL_MOVE, 0.0, GameState::Undecided);
+        }
         
         value = -value;
         
@@ -9,6 +9,8 @@ pub struct BBSettings {
     pub null_move_pruning_margin: f32,
     pub null_move_pruning_depth: u8,
     pub max_extensions: u8,
+    //Count a single repetition inside the search tree as a draw
+    pub twofold_repetition: bool,
     pub eval_factors: EvalFactorsFloat,
     pub min_search_time: u64
 }
@@ -21,6 +23,7 @@ pub const STANDARD_BB_SETTINGS: BBSettings = BBSettings {
     null_move_pruning: true,
     null_move_pruning_margin: 0.3,
     null_move_pruning_depth: 3, 
+    twofold_repetition: true, 
     min_search_time: 0, 
     eval_factors: STANDARD_EVAL_FACTORS 
 };
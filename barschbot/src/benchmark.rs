@@ -1,17 +1,38 @@
 use core::panic;
 use std::time::Instant;
 
-use crate::{bitboard_helper, square::{self, Square}};
+use barschbot::{bitboard_helper, slider_attacks::{self, SliderBackend}, square::{self, Square}};
+use rand::Rng;
+
+const VERIFY_SAMPLES: u32 = 100_000;
 
-    
 pub fn benchmark() {
-    for _ in 0..5 {
-        let start = Instant::now();
-        let res = fill_benchmark();
-        let duration = start.elapsed();
-        println!("Result: {}", res);
-        println!("Time: {:?}", duration);
+    let previous = slider_attacks::get_backend();
+
+    verify_backends(VERIFY_SAMPLES);
+
+    for backend in slider_attacks::ALL_BACKENDS {
+        if backend == SliderBackend::Pext && !slider_attacks::pext_available() {
+            println!("{}: not supported by this cpu", backend.to_string());
+            continue;
+        }
+
+        slider_attacks::set_backend(backend);
+
+        //Builds the tables outside of the measurement
+        bitboard_helper::gen_rook_moves(Square::A1, 0, 0);
+
+        println!("Backend: {}", backend.to_string());
+        for _ in 0..3 {
+            let start = Instant::now();
+            let res = fill_benchmark();
+            let duration = start.elapsed();
+            println!("Result: {}", res);
+            println!("Time: {:?}", duration);
+        }
     }
+
+    slider_attacks::set_backend(previous);
 }
 
 pub fn fill_benchmark() -> u32 {
@@ -22,30 +43,60 @@ pub fn fill_benchmark() -> u32 {
             let allied = (i * 123456789) & bitboard_helper::DIAGONAL_ATTACKS[*sq as usize] | sq.bit_board();
             let opponent = (i * 987654321) & bitboard_helper::DIAGONAL_ATTACKS[*sq as usize] & !allied;
             let v1 = bitboard_helper::gen_rook_moves(*sq, allied, opponent);
-            
-            sum += v1.count_ones();
-            
-             /* 
-            let v2 = bitboard_helper::fill_orthogonal_2(*sq, allied, opponent);
-            if v1 != v2 {
-                println!("Error at square: {}", sq.to_string());
-                println!("Allied:");
+            let v2 = bitboard_helper::gen_bishop_moves(*sq, allied, opponent);
+
+            sum += v1.count_ones() + v2.count_ones();
+        }
+    }
+
+    return sum;
+}
+
+//Every backend has to return exactly the moves of the fill functions
+pub fn verify_backends(samples: u32) {
+    let mut rng = rand::thread_rng();
+
+    for backend in slider_attacks::ALL_BACKENDS {
+        if backend == SliderBackend::Pext && !slider_attacks::pext_available() {
+            continue;
+        }
+
+        slider_attacks::set_backend(backend);
+
+        for _ in 0..samples {
+            //Sparse and dense boards alike
+            let density = rng.gen_range(1..4);
+            let mut occupied = u64::MAX;
+            for _ in 0..density {
+                occupied &= rng.gen::<u64>();
+            }
 
-                bitboard_helper::print_bitboard(allied);
+            let split = rng.gen::<u64>();
 
-                println!("Opponent:");
+            for sq in square::ARRAY.iter().take(64) {
+                //The slider itself is part of its own pieces, like on a real board
+                let allied = occupied & split | sq.bit_board();
+                let opponent = occupied & !split & !sq.bit_board();
 
-                bitboard_helper::print_bitboard(opponent);
+                let rook = bitboard_helper::gen_rook_moves(*sq, allied, opponent);
+                let bishop = bitboard_helper::gen_bishop_moves(*sq, allied, opponent);
 
-                println!("Values:  ");
+                if rook != bitboard_helper::gen_rook_moves_fill(*sq, allied, opponent)
+                    || bishop != bitboard_helper::gen_bishop_moves_fill(*sq, allied, opponent) {
+                    println!("Error at square: {} with backend {}", sq.to_string(), backend.to_string());
+                    println!("Allied:");
 
-                bitboard_helper::print_bitboard(v1);
-                bitboard_helper::print_bitboard(v2);
-                panic!("Error");
+                    bitboard_helper::print_bitboard(allied);
+
+                    println!("Opponent:");
+
+                    bitboard_helper::print_bitboard(opponent);
+
+                    panic!("Error");
+                }
             }
-            */
         }
-    }
 
-    return sum;
-}
\ No newline at end of file
+        println!("{}: {} random boards match", backend.to_string(), samples);
+    }
+}
@@ -1,13 +1,15 @@
 use core::panic;
 use std::char;
 use arrayvec::ArrayVec;
-use graphics::rectangle::square;
 
-use crate::{bitboard_helper::{self, toggle_bit}, chess_move::ChessMove, square::Square, colored_piece_type::ColoredPieceType, piece_type::{self, PieceType}, endgame_table::BoardState, zoberist_hash::ZoberistHash64};
+use crate::{bitboard_helper::{self, toggle_bit}, chess_move::ChessMove, fen::{FenError, FenField}, square::Square, colored_piece_type::ColoredPieceType, piece_type::{self, PieceType}, endgame_table::BoardState, zoberist_hash::ZoberistHash64};
 
 
 
-//const DEBUG: bool = std::cfg!(debug_assertions);
+const DEBUG: bool = std::cfg!(debug_assertions);
+
+//Piece values for the static exchange evaluation
+pub const SEE_VALUES: [i32; 6] = [100, 300, 300, 500, 900, 20_000];
 
 
 #[derive(Clone, Copy)]
@@ -29,18 +31,26 @@ pub struct BitBoard {
     diagonal_sliders: u64,
     kings: u64,
 
-    pub type_field: [ColoredPieceType; 64]
+    pub type_field: [ColoredPieceType; 64],
+
+    //Kept up to date by place_piece, remove_piece and make_move
+    zoberist_hash: ZoberistHash64,
 }
 
 impl BitBoard {
     pub fn empty() -> Self {
-        return BitBoard { whites_turn: true, white_queen_castle: false, white_king_castle: false, black_queen_castle: false, black_king_castle: false,
+        let mut board = BitBoard { whites_turn: true, white_queen_castle: false, white_king_castle: false, black_queen_castle: false, black_king_castle: false,
             en_passant_square: Square::None, 
-            white_pieces: 0, black_pieces: 0, pawns: 0, knights: 0, orthogonal_sliders: 0, diagonal_sliders: 0, kings: 0, type_field: [ColoredPieceType::None; 64] };
+            white_pieces: 0, black_pieces: 0, pawns: 0, knights: 0, orthogonal_sliders: 0, diagonal_sliders: 0, kings: 0, type_field: [ColoredPieceType::None; 64],
+            zoberist_hash: ZoberistHash64::new() };
+
+        board.recalculate_hash();
+
+        return board;
     }
 
     pub fn start_position() -> Self {
-        return Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - -");
+        return Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
     }
 
     pub fn is_whites_turn(&self) -> bool {
@@ -48,6 +58,10 @@ impl BitBoard {
     }
 
     pub fn set_whites_turn(&mut self, whites_turn: bool) {
+        if self.whites_turn != whites_turn {
+            self.zoberist_hash.toggle_turn();
+        }
+
         self.whites_turn = whites_turn;
     }
 
@@ -74,6 +88,7 @@ impl BitBoard {
 
         board.set_whites_turn(bs.whites_turn);
         board.en_passant_square = bs.ep_square;
+        board.recalculate_hash();
 
         return board;
     }
@@ -169,15 +184,28 @@ impl BitBoard {
     }
 
     pub fn is_valid_position(&mut self) -> bool {
+        if (self.kings & self.white_pieces).count_ones() != 1 || (self.kings & self.black_pieces).count_ones() != 1 {
+            return false;
+        }
 
-        let attacks_on_white = self.get_square_attacker(true, self.get_king_square(false));
-        let attacks_on_black = self.get_square_attacker(false, self.get_king_square(true));
+        //Pawns can never stand on the first or last rank
+        if self.pawns & 0xFF000000000000FF != 0 {
+            return false;
+        }
+
+        let attacks_on_white = self.get_square_attacker(false, self.get_king_square(true));
+        let attacks_on_black = self.get_square_attacker(true, self.get_king_square(false));
 
         //both in check
         if attacks_on_white.len() > 0 && attacks_on_black.len() > 0 {
             return false;
         }
 
+        //the side that just moved cant still be in check
+        if (self.whites_turn && attacks_on_black.len() > 0) || (!self.whites_turn && attacks_on_white.len() > 0) {
+            return false;
+        }
+
         if attacks_on_white.len() > 2 || attacks_on_black.len() > 2 {
             return false 
         }
@@ -216,54 +244,143 @@ impl BitBoard {
     }
 
     pub fn from_fen(fen: &str) -> Self {
-        let parts = fen.split(" ").collect::<Vec<_>>();
+        return match Self::try_from_fen(fen) {
+            Ok(board) => board,
+            Err(e) => panic!("Invalid fen \"{}\": {}", fen, e),
+        };
+    }
+
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
+        let (board, _, _) = Self::parse_fen(fen)?;
+
+        return Ok(board);
+    }
+
+    //Returns the board plus halfmove clock and fullmove number, missing clocks default to 0 and 1
+    pub fn parse_fen(fen: &str) -> Result<(Self, u32, u32), FenError> {
+        let parts = fen.split_whitespace().collect::<Vec<_>>();
+
+        if parts.len() > 6 {
+            return Err(FenError::TooManyFields(parts.len()));
+        }
+
+        let field = |index: usize, name: FenField| parts.get(index).map(|s| *s).ok_or(FenError::MissingField(name));
+
         let mut board = BitBoard::empty();
-        let mut square = 64 - 8;
 
-        const ALL_CHARS: &str = "rnbqkpRNBQKP12345678/";
+        let ranks = field(0, FenField::Placement)?.split('/').collect::<Vec<_>>();
+        if ranks.len() != 8 {
+            return Err(FenError::InvalidRankCount(ranks.len()));
+        }
 
-        for c in parts[0].chars() {
-            if ALL_CHARS.find(c) == None {
-                continue;
-            }
-            if c == '/' {
-                square -= 16;
-                continue;
+        for (i, rank) in ranks.iter().enumerate() {
+            let y = 7 - i as u8;
+            let mut x = 0;
+
+            for c in rank.chars() {
+                if x >= 8 {
+                    return Err(FenError::InvalidRankLength(y + 1));
+                }
+
+                match c {
+                    '1'..='8' => x += c as u8 - b'0',
+                    _ => {
+                        let piece = ColoredPieceType::from_char(c);
+                        if piece == ColoredPieceType::None {
+                            return Err(FenError::InvalidPiece(c));
+                        }
+
+                        board.place_piece(piece, Square::from_coords(x, y));
+                        x += 1;
+                    }
+                }
             }
-            
-            let piece = ColoredPieceType::from_char(c);
-            
-            if piece != ColoredPieceType::None {
-                board.place_piece(piece,  Square::from_u8(square));
-                
-                square += 1;
+
+            if x != 8 {
+                return Err(FenError::InvalidRankLength(y + 1));
             }
-            else {
-                square += c.to_string().parse::<u8>().unwrap();
+        }
+
+        if (board.kings & board.white_pieces).count_ones() != 1 || (board.kings & board.black_pieces).count_ones() != 1 {
+            return Err(FenError::InvalidKingCount);
+        }
+
+        board.whites_turn = match field(1, FenField::SideToMove)? {
+            "w" => true,
+            "b" => false,
+            s => return Err(FenError::InvalidSideToMove(s.to_owned())),
+        };
+
+        let castling = field(2, FenField::Castling)?;
+        if castling != "-" {
+            for c in castling.chars() {
+                let (flag, king_square, rook_square, rook) = match c {
+                    'K' => (&mut board.white_king_castle, Square::E1, Square::H1, ColoredPieceType::WhiteRook),
+                    'Q' => (&mut board.white_queen_castle, Square::E1, Square::A1, ColoredPieceType::WhiteRook),
+                    'k' => (&mut board.black_king_castle, Square::E8, Square::H8, ColoredPieceType::BlackRook),
+                    'q' => (&mut board.black_queen_castle, Square::E8, Square::A8, ColoredPieceType::BlackRook),
+                    _ => return Err(FenError::InvalidCastling(castling.to_owned())),
+                };
+
+                if *flag {
+                    return Err(FenError::InvalidCastling(castling.to_owned()));
+                }
+
+                *flag = true;
+
+                //The move generator relies on king and rook still standing on their start squares
+                let king = if rook == ColoredPieceType::WhiteRook { ColoredPieceType::WhiteKing } else { ColoredPieceType::BlackKing };
+                if board.type_field[king_square as usize] != king || board.type_field[rook_square as usize] != rook {
+                    return Err(FenError::InvalidCastling(castling.to_owned()));
+                }
             }
         }
 
-        board.whites_turn = parts[1] == "w";
+        let ep = field(3, FenField::EnPassant)?;
+        if ep != "-" {
+            let chars = ep.chars().collect::<Vec<_>>();
+            //White to move means black just pushed a pawn, so the target is on the 6th rank
+            let expected_rank = if board.whites_turn { '6' } else { '3' };
+
+            if chars.len() != 2 || !('a'..='h').contains(&chars[0]) || chars[1] != expected_rank {
+                return Err(FenError::InvalidEnPassant(ep.to_owned()));
+            }
+
+            let square = Square::from_str(ep);
+            let pawn_square = if board.whites_turn { square as u8 - 8 } else { square as u8 + 8 };
+            let pawn = if board.whites_turn { ColoredPieceType::BlackPawn } else { ColoredPieceType::WhitePawn };
 
-        for c in parts[2].chars() {
-            match c {
-                'K' => board.white_king_castle = true,
-                'Q' => board.white_queen_castle = true,
-                'k' => board.black_king_castle = true,
-                'q' => board.black_queen_castle = true,
-                _ => ()
+            if board.type_field[square as usize] != ColoredPieceType::None || board.type_field[pawn_square as usize] != pawn {
+                return Err(FenError::InvalidEnPassant(ep.to_owned()));
             }
+
+            board.en_passant_square = square;
         }
 
-        if parts[3] != "-" {
-            board.en_passant_square = Square::from_str(parts[3]);
+        let halfmove = match parts.get(4) {
+            Some(s) => s.parse::<u32>().map_err(|_| FenError::InvalidHalfmoveClock(s.to_string()))?,
+            None => 0,
+        };
+
+        let fullmove = match parts.get(5) {
+            Some(s) => match s.parse::<u32>() {
+                Ok(v) if v >= 1 => v,
+                _ => return Err(FenError::InvalidFullmoveNumber(s.to_string())),
+            },
+            None => 1,
+        };
+
+        if !board.is_valid_position() {
+            return Err(FenError::IllegalPosition);
         }
 
-        //println!("Loaded FEN {}", fen);
+        //Side to move, castling and en passant were set directly
+        board.recalculate_hash();
 
-        return board;
+        return Ok((board, halfmove, fullmove));
     }
 
+    //Only the first four fields, the move clocks are tracked by Game::get_fen
     pub fn get_fen(&self) -> String {
         let mut s = "".to_owned();
         for y in (0..8).rev() {
@@ -335,10 +452,19 @@ impl BitBoard {
     }
 
     pub fn get_zoberist_hash(&self) -> u64 {
+        return self.zoberist_hash.get_hash();
+    }
+
+    fn calculate_zoberist_hash(&self) -> u64 {
         return ZoberistHash64::calculate_hash(&self.type_field, self.whites_turn, self.en_passant_square, 
             self.white_queen_castle, self.white_king_castle, self.black_queen_castle, self.black_king_castle);
     }
 
+    fn recalculate_hash(&mut self) {
+        self.zoberist_hash.recalculate_hash(&self.type_field, self.whites_turn, self.en_passant_square, 
+            self.white_queen_castle, self.white_king_castle, self.black_queen_castle, self.black_king_castle);
+    }
+
     fn toggle_piece_bitboards(&mut self, colored_piece_type: ColoredPieceType, square: Square) {
         match PieceType::from_cpt(colored_piece_type) {
             PieceType::Pawn     => toggle_bit(&mut self.pawns, square),
@@ -361,12 +487,14 @@ impl BitBoard {
 
     fn place_piece(&mut self, colored_piece_type: ColoredPieceType, square: Square) {
         self.toggle_piece_bitboards(colored_piece_type, square);
+        self.zoberist_hash.toggle_piece(colored_piece_type, square);
         self.type_field[square as usize] = colored_piece_type;
     }
 
     fn remove_piece(&mut self, square: Square) {
         let cpt = self.type_field[square as usize];
         self.toggle_piece_bitboards(cpt, square);
+        self.zoberist_hash.toggle_piece(cpt, square);
         self.type_field[square as usize] = ColoredPieceType::None;
     }
 
@@ -837,16 +965,16 @@ impl BitBoard {
         //    bitboard_helper::print_bitboard(pair.3);
         //    
         //    
-        //    list.sort_by(|a ,b| a.get_board_name(&self).cmp(&b.get_board_name(&self)));
-        //    fast.sort_by(|a ,b| a.get_board_name(&self).cmp(&b.get_board_name(&self)));
+        //    list.sort_by(|a ,b| a.get_san(&self).cmp(&b.get_san(&self)));
+        //    fast.sort_by(|a ,b| a.get_san(&self).cmp(&b.get_san(&self)));
         //    
         //    for m in list {
-        //        print!("{} ", m.get_board_name(&self));
+        //        print!("{} ", m.get_san(&self));
         //    }
         //    
         //    println!();
         //    for m in fast {
-        //        print!("{} ", m.get_board_name(&self));
+        //        print!("{} ", m.get_san(&self));
         //    }
         //    println!();
         //    
@@ -1253,10 +1381,17 @@ impl BitBoard {
             list.push(m);
         }
 
-        move_piece_type = ColoredPieceType::from_pt(PieceType::King, whites_turn);   
-
-        //Castles
         //not in check
+        self.add_castle_moves(whites_turn, &mut list);
+
+        return list;
+    }
+
+    fn add_castle_moves(&self, whites_turn: bool, list: &mut ArrayVec<ChessMove, 200>) {
+        let king_square = self.get_king_square(whites_turn);
+        let all_mask = self.white_pieces | self.black_pieces;
+        let move_piece_type = ColoredPieceType::from_pt(PieceType::King, whites_turn);
+
         if whites_turn {
             if self.white_queen_castle {
                 if  bitboard_helper::WHITE_QUEEN_CASTLE_MASK & all_mask == 0 && 
@@ -1291,11 +1426,263 @@ impl BitBoard {
                 }
             }
         }
-        
+    }
+
+    //Pseudo legal moves only need is_legal to be played. Captures are captures, en passant and promotions,
+    //quiets are everything else including the castles, which are only generated when they are legal
+    pub fn generate_pseudo_legal_moves(&self) -> ArrayVec<ChessMove, 200> {
+        let mut list = ArrayVec::new();
+        self.add_pseudo_legal_moves(true, &mut list);
+        self.add_pseudo_legal_moves(false, &mut list);
 
         return list;
     }
 
+    pub fn generate_pseudo_legal_captures(&self) -> ArrayVec<ChessMove, 200> {
+        let mut list = ArrayVec::new();
+        self.add_pseudo_legal_moves(true, &mut list);
+
+        return list;
+    }
+
+    pub fn generate_pseudo_legal_quiets(&self) -> ArrayVec<ChessMove, 200> {
+        let mut list = ArrayVec::new();
+        self.add_pseudo_legal_moves(false, &mut list);
+
+        return list;
+    }
+
+    fn add_pseudo_legal_moves(&self, captures: bool, list: &mut ArrayVec<ChessMove, 200>) {
+        let whites_turn = self.whites_turn;
+        let moving_color = if whites_turn { self.white_pieces } else { self.black_pieces };
+        let opponent_mask = if whites_turn { self.black_pieces } else { self.white_pieces };
+        let all_mask = self.white_pieces | self.black_pieces;
+
+        let target_mask = if captures { opponent_mask } else { !all_mask };
+
+        //Pawns
+        let pawns = self.pawns & moving_color;
+        let pawn_direction: i32 = if whites_turn { 1 } else { -1 };
+        let promotion_mask = bitboard_helper::RANK_MASKS[if whites_turn { 7 } else { 0 }];
+        let double_move_mask = bitboard_helper::RANK_MASKS[if whites_turn { 3 } else { 4 }];
+        let move_piece_type = ColoredPieceType::from_pt(PieceType::Pawn, whites_turn);
+
+        fn add_pawn_moves(targets: u64, offset: i32, move_piece_type: ColoredPieceType, type_field: &[ColoredPieceType; 64], list: &mut ArrayVec<ChessMove, 200>) {
+            for index in bitboard_helper::iterate_set_bits(targets) {
+                let target_square = Square::from_u8(index as u8);
+                let start_square = Square::from_u8((index as i32 - offset) as u8);
+                let target_piece_type = type_field[target_square as usize];
+
+                if target_square.rank() == 0 || target_square.rank() == 7 {
+                    for pt in [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight] {
+                        let promotion_piece_type = ColoredPieceType::from_pt(pt, move_piece_type.is_white());
+                        list.push(ChessMove::new_pawn_move(start_square, target_square, move_piece_type, target_piece_type, promotion_piece_type));
+                    }
+                }
+                else {
+                    list.push(ChessMove::new_move(start_square, target_square, move_piece_type, target_piece_type));
+                }
+            }
+        }
+
+        let single_push = !all_mask & bitboard_helper::shift_board(pawns, 0, pawn_direction);
+
+        if captures {
+            let ep_mask = if self.en_passant_square == Square::None { 0 } else { self.en_passant_square.bit_board() };
+
+            add_pawn_moves(single_push & promotion_mask, pawn_direction * 8, move_piece_type, &self.type_field, list);
+            add_pawn_moves((opponent_mask | ep_mask) & bitboard_helper::shift_board(pawns, 1, pawn_direction), pawn_direction * 8 + 1, move_piece_type, &self.type_field, list);
+            add_pawn_moves((opponent_mask | ep_mask) & bitboard_helper::shift_board(pawns, -1, pawn_direction), pawn_direction * 8 - 1, move_piece_type, &self.type_field, list);
+        }
+        else {
+            add_pawn_moves(single_push & !promotion_mask, pawn_direction * 8, move_piece_type, &self.type_field, list);
+
+            let double_push = double_move_mask & !all_mask & bitboard_helper::shift_board(single_push, 0, pawn_direction);
+            add_pawn_moves(double_push, pawn_direction * 16, move_piece_type, &self.type_field, list);
+        }
+
+        //Pieces
+        for start_index in bitboard_helper::iterate_set_bits(moving_color & !self.pawns) {
+            let start_square = Square::from_u8(start_index as u8);
+            let move_piece_type = self.type_field[start_square as usize];
+
+            for target_index in bitboard_helper::iterate_set_bits(self.get_pseudo_legal_piece_attacks(move_piece_type, start_square) & target_mask) {
+                let target_square = Square::from_u8(target_index as u8);
+                list.push(ChessMove::new_move(start_square, target_square, move_piece_type, self.type_field[target_square as usize]));
+            }
+        }
+
+        if !captures && !self.in_check() {
+            self.add_castle_moves(whites_turn, list);
+        }
+    }
+
+    //Bitboard of the pieces giving check to the side of white
+    fn get_checkers(&self, white: bool) -> u64 {
+        let mut checkers = 0;
+        for square in self.get_square_attacker(!white, self.get_king_square(white)) {
+            checkers |= square.bit_board();
+        }
+
+        return checkers;
+    }
+
+    //Legality of a pseudo legal move of the side to move, only en passant falls back to playing the move
+    pub fn is_legal(&self, m: ChessMove) -> bool {
+        let white = self.whites_turn;
+        let king_square = self.get_king_square(white);
+
+        if PieceType::from_cpt(m.move_piece_type) == PieceType::King {
+            //Castles are only generated when the king path is safe
+            if m.is_castle() {
+                return true;
+            }
+
+            return !self.square_is_attacked_by_ignore_king(!white, m.target_square);
+        }
+
+        //Removes two pieces from the same rank, the pin masks dont cover that
+        if m.is_en_passant() {
+            return self.move_is_legal(m);
+        }
+
+        let checkers = self.get_checkers(white);
+        if checkers.count_ones() > 1 {
+            return false;
+        }
+
+        //Single check: capture the checker or block it
+        if checkers != 0 {
+            let checker = Square::from_u8(checkers.trailing_zeros() as u8);
+
+            if m.target_square != checker && !bitboard_helper::get_bit(bitboard_helper::get_in_between(king_square, checker), m.target_square) {
+                return false;
+            }
+        }
+
+        //Only pieces on a line with the king can be pinned
+        if !bitboard_helper::get_bit(bitboard_helper::QUEEN_ATTACKS[king_square as usize], m.start_square) {
+            return true;
+        }
+
+        let (d_pins, o_pins) = self.get_pin_info(white);
+        if !bitboard_helper::get_bit(d_pins | o_pins, m.start_square) {
+            return true;
+        }
+
+        //Pinned pieces have to stay on the line between king and pinner
+        return bitboard_helper::get_bit(bitboard_helper::get_in_between(king_square, m.target_square), m.start_square) ||
+            bitboard_helper::get_bit(bitboard_helper::get_in_between(king_square, m.start_square), m.target_square);
+    }
+
+    //Whether m could have been generated in this position, used to check moves from the transposition table
+    pub fn is_pseudo_legal(&self, m: ChessMove) -> bool {
+        if m.is_null_move() || m.move_piece_type == ColoredPieceType::None || m.move_piece_type.is_white() != self.whites_turn {
+            return false;
+        }
+
+        if self.type_field[m.start_square as usize] != m.move_piece_type || self.type_field[m.target_square as usize] != m.capture_piece_type {
+            return false;
+        }
+
+        if PieceType::from_cpt(m.move_piece_type) == PieceType::Pawn || m.is_castle() {
+            let list = if m.is_capture() || m.is_promotion() { self.generate_pseudo_legal_captures() } else { self.generate_pseudo_legal_quiets() };
+            return list.contains(&m);
+        }
+
+        return bitboard_helper::get_bit(self.get_pseudo_legal_piece_attacks(m.move_piece_type, m.start_square), m.target_square);
+    }
+
+    //Material balance of the capture sequence on the target square of m, both sides always recapture 
+    //with their least valuable piece and may stop when going on would lose material. Pins are ignored
+    pub fn see(&self, m: ChessMove) -> i32 {
+        let target_square = m.target_square;
+        let mut occupied = (self.white_pieces | self.black_pieces) & !m.start_square.bit_board();
+
+        let mut gain = [0_i32; 32];
+        gain[0] = get_see_value(m.capture_piece_type);
+
+        if m.is_en_passant() {
+            let pawn_square = if m.is_white_move() { target_square as u8 - 8 } else { target_square as u8 + 8 };
+            occupied &= !Square::from_u8(pawn_square).bit_board();
+            gain[0] = SEE_VALUES[PieceType::Pawn as usize];
+        }
+
+        //Value of the piece standing on the target square after each capture
+        let mut on_square = get_see_value(m.move_piece_type);
+        if m.is_promotion() {
+            on_square = get_see_value(m.promotion_piece_type);
+            gain[0] += on_square - SEE_VALUES[PieceType::Pawn as usize];
+        }
+
+        let mut attackers = 0;
+        for white in [true, false] {
+            for square in self.get_square_attacker(white, target_square) {
+                attackers |= square.bit_board();
+            }
+        }
+
+        //The moving piece may have been in front of a slider
+        attackers |= bitboard_helper::gen_bishop_moves(target_square, 0, occupied) & self.diagonal_sliders;
+        attackers |= bitboard_helper::gen_rook_moves(target_square, 0, occupied) & self.orthogonal_sliders;
+        attackers &= occupied;
+
+        let mut white = !m.is_white_move();
+        let mut depth = 0;
+
+        loop {
+            let side_mask = if white { self.white_pieces } else { self.black_pieces };
+            let side_attackers = attackers & side_mask;
+            if side_attackers == 0 {
+                break;
+            }
+
+            //Least valuable attacker
+            let mut attacker = 0;
+            let mut attacker_type = PieceType::None;
+            for (pt, bits) in [(PieceType::Pawn, self.pawns), (PieceType::Knight, self.knights), 
+                (PieceType::Bishop, self.diagonal_sliders & !self.orthogonal_sliders), (PieceType::Rook, self.orthogonal_sliders & !self.diagonal_sliders),
+                (PieceType::Queen, self.diagonal_sliders & self.orthogonal_sliders), (PieceType::King, self.kings)] {
+                
+                if side_attackers & bits != 0 {
+                    attacker = 1_u64 << (side_attackers & bits).trailing_zeros();
+                    attacker_type = pt;
+                    break;
+                }
+            }
+
+            depth += 1;
+            gain[depth] = on_square - gain[depth - 1];
+            on_square = SEE_VALUES[attacker_type as usize];
+
+            occupied &= !attacker;
+
+            //Sliders behind the piece that just captured join in
+            if attacker_type == PieceType::Pawn || attacker_type == PieceType::Bishop || attacker_type == PieceType::Queen {
+                attackers |= bitboard_helper::gen_bishop_moves(target_square, 0, occupied) & self.diagonal_sliders;
+            }
+
+            if attacker_type == PieceType::Rook || attacker_type == PieceType::Queen {
+                attackers |= bitboard_helper::gen_rook_moves(target_square, 0, occupied) & self.orthogonal_sliders;
+            }
+
+            attackers &= occupied;
+            white = !white;
+        }
+
+        //Each side can choose to stop capturing
+        while depth > 0 {
+            gain[depth - 1] = -i32::max(-gain[depth - 1], gain[depth]);
+            depth -= 1;
+        }
+
+        return gain[0];
+    }
+
+    pub fn see_ge(&self, m: ChessMove, threshold: i32) -> bool {
+        return self.see(m) >= threshold;
+    }
+
     pub fn generate_legal_moves_eval(&self, whites_turn: bool) -> ArrayVec<ChessMove, 200> {
         let mut list = ArrayVec::new();
 
@@ -1612,12 +1999,18 @@ impl BitBoard {
     }
     
     pub fn make_move(&mut self, m: ChessMove) {
+        //Pieces are hashed by place_piece and remove_piece, the rest is swapped out here
+        self.zoberist_hash.toggle_en_passant(self.en_passant_square);
+        self.zoberist_hash.toggle_castling(self.white_queen_castle, self.white_king_castle, self.black_queen_castle, self.black_king_castle);
+
         if m.is_null_move() {
             self.en_passant_square = Square::None;
             self.whites_turn = !self.whites_turn;
 
             //println!("kek");
 
+            self.finish_hash_update();
+
             return;
         }
 
@@ -1707,6 +2100,18 @@ impl BitBoard {
         }
 
         self.whites_turn = !self.whites_turn;
+
+        self.finish_hash_update();
+    }
+
+    fn finish_hash_update(&mut self) {
+        self.zoberist_hash.toggle_en_passant(self.en_passant_square);
+        self.zoberist_hash.toggle_castling(self.white_queen_castle, self.white_king_castle, self.black_queen_castle, self.black_king_castle);
+        self.zoberist_hash.toggle_turn();
+
+        if DEBUG {
+            assert_eq!(self.zoberist_hash.get_hash(), self.calculate_zoberist_hash(), "Incremental hash is out of sync: {}", self.get_fen());
+        }
     }
 
     //Does not check castle move square and start square
@@ -1824,7 +2229,7 @@ impl BitBoard {
         print!("Moves {}[", list.len());
     
         for m in list {
-            print!("{} ", m.get_board_name(self));  
+            print!("{} ", m.get_san(self));  
         }
     
         println!("]");
@@ -1868,4 +2273,12 @@ impl BitBoard {
         return false;
 
     }
-}
\ No newline at end of file
+}
+
+fn get_see_value(cpt: ColoredPieceType) -> i32 {
+    if cpt == ColoredPieceType::None {
+        return 0;
+    }
+
+    return SEE_VALUES[PieceType::from_cpt(cpt) as usize];
+}
@@ -2,7 +2,7 @@ use core::panic;
 
 use bitintr::{Lzcnt, T1mskc};
 
-use crate::{constants::BISHOP, fill_arrays, square::{self, Square}};
+use crate::{constants::BISHOP, fill_arrays, slider_attacks::{self, SliderBackend}, square::{self, Square}};
 
 pub fn set_bit(bit_board: &mut u64, square: Square, value: bool) {
     debug_assert!(square != Square::None);
@@ -185,7 +185,22 @@ pub fn fill_down_left(mut gen: u64, mut free: u64) -> u64 {
     return gen;
 }
 
+//Dispatches to the backend picked in slider_attacks, all of them return the same moves
 pub fn gen_rook_moves(square: Square, allied: u64, opponent: u64) -> u64 {
+    return match slider_attacks::get_backend() {
+        SliderBackend::Fill => gen_rook_moves_fill(square, allied, opponent),
+        backend => slider_attacks::get_rook_attacks(backend, square, allied | opponent) & !allied,
+    };
+}
+
+pub fn gen_bishop_moves(square: Square, allied: u64, opponent: u64) -> u64 {
+    return match slider_attacks::get_backend() {
+        SliderBackend::Fill => gen_bishop_moves_fill(square, allied, opponent),
+        backend => slider_attacks::get_bishop_attacks(backend, square, allied | opponent) & !allied,
+    };
+}
+
+pub fn gen_rook_moves_fill(square: Square, allied: u64, opponent: u64) -> u64 {
     let bb = square.bit_board();
     let mut next = 0;
     let free = !(allied | opponent);
@@ -201,7 +216,7 @@ pub fn gen_rook_moves(square: Square, allied: u64, opponent: u64) -> u64 {
         (next << 8)) & ORTHOGONAL_ATTACKS[square as usize] & opponent) & !bb;
 }
 
-pub fn gen_bishop_moves(square: Square, allied: u64, opponent: u64) -> u64 {  
+pub fn gen_bishop_moves_fill(square: Square, allied: u64, opponent: u64) -> u64 {  
     let bb = square.bit_board();
     let mut next = 0;
     let free = !(allied | opponent);
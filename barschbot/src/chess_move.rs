@@ -1,4 +1,4 @@
-use crate::{constants, bit_board::{self, BitBoard}, square::Square, colored_piece_type::ColoredPieceType, piece_type::PieceType};
+use crate::{constants, bit_board::{self, BitBoard}, san::{self, SanError}, square::Square, colored_piece_type::ColoredPieceType, piece_type::PieceType};
 use std::num;
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -96,51 +96,12 @@ impl ChessMove {
         return x;
     }
 
-    pub fn get_board_name(&self, board: &BitBoard) -> String {
-        if self.is_null_move() {
-            return "NULL_MOVE".to_owned();
-        }
-
-        if self.is_castle() {
-            if (self.target_square as u8) < (self.start_square as u8) {
-                return "O-O-O".to_owned();
-            }
-            else {
-                return "O-O".to_owned();
-            }
-        }
-
-        let moves = board.get_legal_moves();
-
-        
-        let mut s = "".to_owned();
-
-
-
-        if PieceType::from_cpt(self.move_piece_type) == PieceType::Pawn {
-            if self.is_direct_capture() || self.is_en_passant() {
-                s += &self.start_square.file_char().to_string();
-            }
-        }
-        else {
-            s += &PieceType::from_cpt(self.move_piece_type).get_char().to_string();
-        }
-        
-
-        for m in moves {
-            if m.target_square == self.target_square && m.move_piece_type == self.move_piece_type && m.start_square != self.start_square && m.move_piece_type.get_piece_type() != PieceType::Pawn {
-                s += &self.start_square.to_string();
-                break;
-            }
-        }
-
-        if self.is_direct_capture() || self.is_en_passant() {
-            s += "x";
-        }
-
-        s += &self.target_square.to_string();
+    pub fn get_san(&self, board: &BitBoard) -> String {
+        return san::get_san(*self, board);
+    }
 
-        return s;
+    pub fn from_san(san: &str, board: &BitBoard) -> Result<ChessMove, SanError> {
+        return san::parse_san(san, board);
     }
 
     pub fn print(&self) {
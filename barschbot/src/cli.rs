@@ -0,0 +1,102 @@
+use std::{collections::HashMap, process, str::FromStr};
+
+pub const USAGE: &str = "Usage: barschbot <command> [options]
+
+Commands:
+    uci            Run KarpfenBot as a UCI engine on stdin/stdout
+    perft          Count leaf nodes of --fen (--divide for per move counts) or check the standard
+                   positions / an --epd perft suite up to --depth (--hash to use a table)
+    bench          Check and time the slider attack backends
+    puzzles        Let BarschBot solve a lichess puzzle csv (--puzzles)
+    match          Play BarschBot against KarpfenBot from every position in --fens (--pgn to archive the games)
+    tune           Tune the BarschBot eval factors against stockfish (--fens, --stockfish)
+    gen-egtb       Generate the endgame table for --pieces pieces into --table
+    play           Play against a bot in the visualizer (--fen, --black, --bot barsch|karpfen), needs --features gui
+    random-games   Print the result distribution of random games
+
+Options:
+    --book <file>        Opening book (book.txt)
+    --table <dir>        Directory containing sorted_table_base_<n>.bin
+    --pieces <n>         Endgame table piece count (default 4)
+    --fens <file>        Start positions, one fen per line
+    --fen <fen>          Single position
+    --puzzles <file>     lichess_db_puzzle.csv
+    --pgn <file>         Append finished games to this pgn file
+    --stockfish <path>   Stockfish executable (default stockfish)
+    --depth <n>          Search / perft depth
+    --threads <n>        Worker threads (default: available cores)
+    --disable <list>     KarpfenBot search features to turn off, comma separated: pvs, lmr, nmp,
+                         adaptive-nmp, nmp-verification, rfp, razoring, futility, lmp
+    --game               perft: use Game instead of BitBoard
+    --divide             perft: node count for every root move of --fen
+    --epd <file>         perft: suite, lines like <fen> ;D1 20 ;D2 400
+    --hash <mb>          perft: transposition table size
+    --slider <name>      Slider attack backend: fill, magic (default) or pext";
+
+pub struct CliArgs {
+    pub command: String,
+    options: HashMap<String, String>
+}
+
+impl CliArgs {
+    pub fn parse(args: &[String]) -> CliArgs {
+        let command = args.get(0).cloned().unwrap_or("help".to_owned());
+        let mut options = HashMap::new();
+
+        let mut i = 1;
+        while i < args.len() {
+            let key = match args[i].strip_prefix("--") {
+                Some(key) => key.to_owned(),
+                None => exit_with_error(&format!("Unexpected argument: {}", args[i])),
+            };
+
+            //Options without a value are flags
+            match args.get(i + 1) {
+                Some(value) if !value.starts_with("--") => {
+                    options.insert(key, value.clone());
+                    i += 2;
+                },
+                _ => {
+                    options.insert(key, String::new());
+                    i += 1;
+                }
+            }
+        }
+
+        return CliArgs { command, options };
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        return self.options.get(key).map(|s| s.as_str());
+    }
+
+    pub fn get_required(&self, key: &str) -> &str {
+        return match self.get(key) {
+            Some(value) if value.len() > 0 => value,
+            _ => exit_with_error(&format!("Missing option --{} for command {}", key, self.command)),
+        };
+    }
+
+    pub fn get_number<T: FromStr>(&self, key: &str) -> Option<T> {
+        return self.get(key).map(|value| match value.parse() {
+            Ok(v) => v,
+            Err(_) => exit_with_error(&format!("Invalid value for --{}: {}", key, value)),
+        });
+    }
+
+    pub fn has_flag(&self, key: &str) -> bool {
+        return self.options.contains_key(key);
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        let available = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+        return self.get_number("threads").unwrap_or(available).max(1);
+    }
+}
+
+pub fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("Run `barschbot help` for a list of commands");
+    process::exit(2);
+}
@@ -1,5 +1,3 @@
-use graphics::types::Color;
-
 use crate::piece_type::{self, PieceType};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
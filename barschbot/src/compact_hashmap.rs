@@ -25,7 +25,11 @@ impl CompactHashmap {
     }
 
     pub fn from_hashmap(mut data: HashMap<u64, i8>) -> CompactHashmap {
-        return CompactHashmap::new(data.drain().collect::<Vec<_>>());
+        //new() and the stored table files expect the keys in ascending order
+        let mut sorted = data.drain().collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|x| x.0);
+
+        return CompactHashmap::new(sorted);
     }
     pub fn len(&self) -> usize {
         return self.keys.len();
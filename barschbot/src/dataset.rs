@@ -1,6 +1,7 @@
-use crate::evaluation::{generate_eval_attributes, generate_eval_attributes_fast, static_eval_float};
+use barschbot::evaluation::{generate_eval_attributes, generate_eval_attributes_fast, static_eval_float};
 use crate::perceptron_float;
-use crate::{bit_board::BitBoard, barsch_bot, game::Game, perceptron_float::Perceptron};
+use barschbot::{bit_board::BitBoard, barsch_bot, game::Game};
+use crate::perceptron_float::Perceptron;
 use std::cmp;
 use std::fs::{read_to_string, self};
 use rand::{thread_rng, Rng};
@@ -28,7 +29,13 @@ impl EvalBoards {
                 continue;
             }
 
-            let mut board = BitBoard::from_fen(parts[0]);
+            let mut board = match BitBoard::try_from_fen(parts[0]) {
+                Ok(board) => board,
+                Err(e) => {
+                    println!("Skipping {}: {}", parts[0], e);
+                    continue;
+                }
+            };
             
             if !barsch_bot::is_quiet_pos(&mut board) {
                 continue;
@@ -1,5 +1,5 @@
 use core::panic;
-use std::{collections::HashMap, fs::File, io::{Write, Read}};
+use std::{collections::HashMap, fs::File, io::{Write, Read}, path::{Path, PathBuf}};
 
 use std::{thread, time};
 
@@ -521,7 +521,7 @@ pub struct EndgameTable {
 
 
 impl EndgameTable {
-    pub fn new(sorted_positions: &Vec<Vec<BoardState>>) -> EndgameTable {
+    pub fn new(sorted_positions: &Vec<Vec<BoardState>>, max_piece_count: u8) -> EndgameTable {
         let mut table_map = HashMap::new(); 
         
         for set in sorted_positions {
@@ -697,7 +697,7 @@ impl EndgameTable {
             }
         }
         
-        return EndgameTable { table_map: CompactHashmap::from_hashmap(table_map), max_piece_count: MAX_PIECE_COUNT };
+        return EndgameTable { table_map: CompactHashmap::from_hashmap(table_map), max_piece_count };
 
         fn wait() {
             let ten_millis = time::Duration::from_millis(1000);
@@ -707,8 +707,16 @@ impl EndgameTable {
         }
     }
 
-    pub fn store_data(&self) {
-        let mut file = File::create("sorted_table_base_".to_owned() + &self.max_piece_count.to_string().to_owned() + ".bin").unwrap();
+    pub fn empty() -> Self {
+        return EndgameTable { table_map: CompactHashmap::empty(), max_piece_count: 0 };
+    }
+
+    pub fn get_file_path(dir: &str, max_piece_count: u8) -> PathBuf {
+        return Path::new(dir).join("sorted_table_base_".to_owned() + &max_piece_count.to_string() + ".bin");
+    }
+
+    pub fn store_data(&self, dir: &str) {
+        let mut file = File::create(Self::get_file_path(dir, self.max_piece_count)).unwrap();
         
         let mut buffer = Vec::with_capacity(self.table_map.len()); 
 
@@ -718,18 +726,22 @@ impl EndgameTable {
         file.write_all(&buffer).unwrap();
     }
 
-    pub fn load(mut max_piece_count: u8) -> Self {
+    pub fn load(dir: &str, mut max_piece_count: u8) -> Self {
         if max_piece_count < 3 {
-            return EndgameTable { table_map: CompactHashmap::empty(), max_piece_count: 0};
+            return Self::empty();
         }
 
-        if max_piece_count > 4 {
+        if max_piece_count > MAX_PIECE_COUNT {
             panic!("I cant handle this anymore");
         }
 
         let start = time::Instant::now();
 
-        let mut file = File::open("C:\\Users\\hmart\\Documents\\GitHub\\BarschBot\\data\\sorted_table_base_".to_owned() + &max_piece_count.to_string().to_owned() + ".bin").unwrap();
+        let path = Self::get_file_path(dir, max_piece_count);
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => panic!("Could not open endgame table {}: {}", path.display(), e),
+        };
         // read the same file back into a Vec of bytes
         let mut buffer = Vec::<u8>::new();
         file.read_to_end(&mut buffer).unwrap();
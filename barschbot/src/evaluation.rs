@@ -331,7 +331,7 @@ pub fn generate_eval_attributes(board: &BitBoard) -> EvalAttributes {
 
         let is_safe = see >= 0;
         
-        //println!("{} is {}", m.get_board_name(board), if is_safe {"safe"} else {"unsafe"});
+        //println!("{} is {}", m.get_san(board), if is_safe {"safe"} else {"unsafe"});
 
         if is_safe {
             safe_mobility_count[mpt as usize] += 1;
@@ -744,7 +744,7 @@ mod tests {
     }
     #[test]
     fn test_generate_eval_attributes_symmetric_full_board() {
-        let board = BitBoard::from_fen("2k1rb1r/pbpnqpp1/1p3n2/3pp2p/3PP2P/1P3N2/PBPNQPP1/2K1RB1R w Kk - 0 1");
+        let board = BitBoard::from_fen("2k1rb1r/pbpnqpp1/1p3n2/3pp2p/3PP2P/1P3N2/PBPNQPP1/2K1RB1R w - - 0 1");
         let attributes = generate_eval_attributes(&board);
 
         // Check that all attributes are zero for an empty board
@@ -824,18 +824,19 @@ mod tests {
     
     #[test]
     fn test_king_safety() {
-        //https://lichess.org/editor/1kp5/1pp5/8/2br2n1/8/8/5P1P/6K1_w_-_-_0_1?color=white
-        let board = BitBoard::from_fen("1kp5/1pp5/8/2br2n1/8/8/5P1P/6K1 w - - 0 1");
+        //https://lichess.org/editor/1kb5/1pp5/8/2br2n1/8/8/5P1P/6K1_w_-_-_0_1?color=white
+        let board = BitBoard::from_fen("1kb5/1pp5/8/2br2n1/8/8/5P1P/6K1 w - - 0 1");
         let attributes = generate_eval_attributes(&board);
 
-        //3n + 11r + 2b - (3n + 3r + 2b)
-        assert_eq!(attributes.king_qn_moves_dif, 8);
+        //Squares taken by own pieces do not count
+        //3n + 11r - (3n + 1r + 1b)
+        assert_eq!(attributes.king_qn_moves_dif, 9);
         //-1
         assert_eq!(attributes.king_control_dif, -1);
         //2n + 1b + 2r
         assert_eq!(attributes.safe_check_dif, -4);
         //0
-        assert_eq!(attributes.unsafe_check_dif, -1);
+        assert_eq!(attributes.unsafe_check_dif, 0);
     }
 
     #[test]
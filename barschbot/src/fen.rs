@@ -0,0 +1,91 @@
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FenField {
+    Placement,
+    SideToMove,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+}
+
+impl FenField {
+    pub fn to_string(&self) -> &str {
+        return match *self {
+            FenField::Placement => "piece placement",
+            FenField::SideToMove => "side to move",
+            FenField::Castling => "castling rights",
+            FenField::EnPassant => "en passant square",
+            FenField::HalfmoveClock => "halfmove clock",
+            FenField::FullmoveNumber => "fullmove number",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    MissingField(FenField),
+    TooManyFields(usize),
+
+    InvalidPiece(char),
+    //rank is counted from the top like in the fen string, 8 = first rank listed
+    InvalidRankLength(u8),
+    InvalidRankCount(usize),
+    InvalidKingCount,
+
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+
+    //Parsed fine but BitBoard::is_valid_position rejected it
+    IllegalPosition,
+}
+
+impl FenError {
+    pub fn get_field(&self) -> FenField {
+        return match self {
+            FenError::MissingField(field) => *field,
+            FenError::TooManyFields(_) => FenField::FullmoveNumber,
+
+            FenError::InvalidPiece(_) => FenField::Placement,
+            FenError::InvalidRankLength(_) => FenField::Placement,
+            FenError::InvalidRankCount(_) => FenField::Placement,
+            FenError::InvalidKingCount => FenField::Placement,
+
+            FenError::InvalidSideToMove(_) => FenField::SideToMove,
+            FenError::InvalidCastling(_) => FenField::Castling,
+            FenError::InvalidEnPassant(_) => FenField::EnPassant,
+            FenError::InvalidHalfmoveClock(_) => FenField::HalfmoveClock,
+            FenError::InvalidFullmoveNumber(_) => FenField::FullmoveNumber,
+
+            FenError::IllegalPosition => FenField::Placement,
+        }
+    }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            FenError::MissingField(field) => write!(f, "missing {}", field.to_string()),
+            FenError::TooManyFields(count) => write!(f, "expected at most 6 fields, found {}", count),
+
+            FenError::InvalidPiece(c) => write!(f, "invalid piece placement: unknown character '{}'", c),
+            FenError::InvalidRankLength(rank) => write!(f, "invalid piece placement: rank {} does not have 8 squares", rank),
+            FenError::InvalidRankCount(count) => write!(f, "invalid piece placement: expected 8 ranks, found {}", count),
+            FenError::InvalidKingCount => write!(f, "invalid piece placement: each side needs exactly one king"),
+
+            FenError::InvalidSideToMove(s) => write!(f, "invalid side to move: \"{}\"", s),
+            FenError::InvalidCastling(s) => write!(f, "invalid castling rights: \"{}\"", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant square: \"{}\"", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock: \"{}\"", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number: \"{}\"", s),
+
+            FenError::IllegalPosition => write!(f, "illegal position"),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
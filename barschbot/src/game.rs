@@ -1,8 +1,8 @@
-use std::{collections::HashSet, fmt};
+use std::fmt;
 
 use arrayvec::ArrayVec;
 
-use crate::{chess_move::{self, ChessMove}, constants, bitboard_helper, bit_board::BitBoard, piece_type::PieceType};
+use crate::{chess_move::{self, ChessMove}, constants, bitboard_helper, bit_board::BitBoard, fen::FenError, game_result::GameResult, piece_type::PieceType};
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum GameState  {
@@ -36,13 +36,16 @@ impl GameState {
     }
 }
 
+#[derive(Clone)]
 pub struct Game {
-    board_history: HashSet<u64>,
-    second_board_history: HashSet<u64>,
+    //Zoberist hash of every board in board_stack
+    hash_stack: Vec<u64>,
     board_stack: Vec<BitBoard>,
     move_stack: Vec<ChessMove>,
     dmc_stack: Vec<u32>,
     board: BitBoard,
+    //Plies played before the first board of the stack, derived from the fen fullmove number
+    start_ply: u32,
     
     moves_generated: bool,
     cached_moves: ArrayVec<ChessMove, 200>,
@@ -51,26 +54,16 @@ pub struct Game {
 
 impl  Game {
     pub fn from_fen(fen: &str) -> Self {
-        let parts = fen.split(" ").collect::<Vec<_>>();
-
-        let mut board = BitBoard::from_fen(fen);
-
-        let mut dmc = 0;
-        
-        if parts.len() >= 5 {
-            if parts[4].len() > 0 {
-                dmc = parts[4].parse::<u32>().unwrap();
-            }
-        }
-        
-        let mut dmc_stack = Vec::new();
-        dmc_stack.push(dmc);
+        return match Self::try_from_fen(fen) {
+            Ok(game) => game,
+            Err(e) => panic!("Invalid fen \"{}\": {}", fen, e),
+        };
+    }
 
-        let mut white_pawns_bitboard = 0;
-        let mut black_pawns_bitboard = 0;
+    pub fn try_from_fen(fen: &str) -> Result<Self, FenError> {
+        let (board, dmc, fullmove) = BitBoard::parse_fen(fen)?;
 
-        return Game { board_history: HashSet::new(), second_board_history: HashSet::new(), board_stack: Vec::new(), move_stack: Vec::new(), board, dmc_stack, 
-            cached_moves: ArrayVec::new(), moves_generated: false }
+        return Ok(Game::from_board_with_clocks(board, dmc, fullmove));
     }
 
     pub fn last_move(&self) -> ChessMove {
@@ -78,15 +71,17 @@ impl  Game {
     }
 
     pub fn from_board(board: BitBoard) -> Self {
-        let mut dmc = 0;        
+        return Game::from_board_with_clocks(board, 0, 1);
+    }
+
+    pub fn from_board_with_clocks(board: BitBoard, halfmove_clock: u32, fullmove_number: u32) -> Self {
         let mut dmc_stack = Vec::new();
-        dmc_stack.push(dmc);
+        dmc_stack.push(halfmove_clock);
 
-        let mut white_pawns_bitboard = 0;
-        let mut black_pawns_bitboard = 0;
+        let start_ply = (fullmove_number.max(1) - 1) * 2 + if board.is_whites_turn() { 0 } else { 1 };
 
-        return Game { board_history: HashSet::new(), second_board_history: HashSet::new(), board_stack: Vec::new(), move_stack: Vec::new(), board, dmc_stack, 
-            cached_moves: ArrayVec::new(), moves_generated: false }
+        return Game { hash_stack: Vec::new(), board_stack: Vec::new(), move_stack: Vec::new(), board, dmc_stack, 
+            start_ply, cached_moves: ArrayVec::new(), moves_generated: false }
     }
 
     pub fn is_whites_turn(&self) -> bool {
@@ -113,8 +108,9 @@ impl  Game {
     }
 
     pub fn make_move(&mut self, m: ChessMove) {
-
-        assert!(self.get_game_state() == GameState::Undecided);
+        //Drawn positions may still be played on (e.g. games loaded from pgn), only mate and stalemate end the game for good.
+        //Only checked in debug builds, the search plays moves without generating the full legal move list
+        debug_assert!(self.get_legal_moves().len() > 0);
 
         if m.is_null_move() {
             if self.board.in_check() {
@@ -133,11 +129,7 @@ impl  Game {
         //update stacks
         self.dmc_stack.push(dmc);
         self.board_stack.push(self.board);
-
-        let hash = self.board.get_zoberist_hash();
-        if !self.board_history.insert(self.board.get_zoberist_hash()) {
-            self.second_board_history.insert(hash);
-        }
+        self.hash_stack.push(self.board.get_zoberist_hash());
         self.move_stack.push(m);
 
         //make move
@@ -151,25 +143,35 @@ impl  Game {
         self.dmc_stack.pop();
 
         self.board = self.board_stack.pop().unwrap();
-
-        let hash = self.board.get_zoberist_hash();
-        if !self.second_board_history.remove(&hash) {
-            self.board_history.remove(&hash);
-        }
-
+        self.hash_stack.pop();
         self.move_stack.pop();
 
         self.moves_generated = false;
     }
 
+    //Full six field fen, null moves count as a ply like any other move
+    pub fn get_fen(&self) -> String {
+        return format!("{} {} {}", self.board.get_fen(), self.fifty_move_counter(), self.get_fullmove_number());
+    }
+
+    pub fn get_start_fen(&self) -> String {
+        let start_board = if self.board_stack.len() > 0 { self.board_stack[0] } else { self.board };
+
+        return format!("{} {} {}", start_board.get_fen(), self.dmc_stack[0], self.start_ply / 2 + 1);
+    }
+
+    pub fn get_fullmove_number(&self) -> u32 {
+        return (self.start_ply + self.move_stack.len() as u32) / 2 + 1;
+    }
+
     pub fn to_string(&self) -> String {
         let mut s = "[Fen \"".to_owned();
-        s += &self.board_stack[0].get_fen();
+        s += &self.get_start_fen();
         s += "\"]";
 
         s += "\n";
         for i in 0..self.board_stack.len() {
-            s += &self.move_stack[i].get_board_name(&self.board_stack[i]);
+            s += &self.move_stack[i].get_san(&self.board_stack[i]);
             
             s += " ";
         }
@@ -180,6 +182,7 @@ impl  Game {
     pub fn get_legal_moves(&mut self) -> ArrayVec<ChessMove, 200> {
         if !self.moves_generated {
             self.cached_moves = self.board.get_legal_moves();
+            self.moves_generated = true;
         }
 
         return self.cached_moves.clone();
@@ -197,6 +200,10 @@ impl  Game {
         return chess_move::NULL_MOVE;
     }
 
+    pub fn get_moves(&self) -> &[ChessMove] {
+        return &self.move_stack;
+    }
+
     pub fn fifty_move_counter(&self) -> u32 {
         return *self.dmc_stack.last().unwrap();
     }
@@ -205,8 +212,71 @@ impl  Game {
         return self.move_stack.len() as u32;
     }
 
-    //[TODO] insuff material
+    pub fn get_game_result(&mut self) -> Option<GameResult> {
+        return GameResult::from_game_state(self.get_game_state());
+    }
+
+    //How often the current position occurred before, only counting boards reached after since_depth moves.
+    //Nothing before the last capture, pawn move or null move can come back, so the search stops there
+    pub fn get_repetition_count(&self, since_depth: u32) -> u32 {
+        let hash = self.board.get_zoberist_hash();
+        let depth = self.board_stack.len();
+        let window = usize::min(self.fifty_move_counter() as usize, depth);
+
+        let mut count = 0;
+        for ply in 1..=window {
+            let index = depth - ply;
+
+            if index < since_depth as usize || self.move_stack[index].is_null_move() {
+                break;
+            }
+
+            //Only every second board has the same side to move
+            if ply % 2 == 0 && self.hash_stack[index] == hash {
+                count += 1;
+            }
+        }
+
+        return count;
+    }
+
+    //Draw by the rules of the game, the third occurrence of a position
+    pub fn is_threefold_repetition(&self) -> bool {
+        return self.get_repetition_count(0) >= 2;
+    }
+
+    //Repetition rule for the engines. With twofold a single repetition already counts as a draw, 
+    //but only if the earlier occurrence is inside the search tree (after the root at root_depth).
+    //Repetitions of positions from the game history still need three occurrences
+    pub fn is_search_repetition(&self, root_depth: u32, twofold: bool) -> bool {
+        if twofold && self.get_repetition_count(root_depth + 1) > 0 {
+            return true;
+        }
+
+        return self.is_threefold_repetition();
+    }
+
+    //Draws the search can see without generating moves, mate and stalemate are found by the move loop
+    pub fn is_search_draw(&self, root_depth: u32, twofold: bool) -> bool {
+        return self.is_search_repetition(root_depth, twofold) || self.fifty_move_counter() >= 100 || self.board.insufficient_material();
+    }
+
+    //Arbiter view of the game, uses threefold repetition
     pub fn get_game_state(&mut self) -> GameState {
+        let repetition = self.is_threefold_repetition();
+
+        return self.get_state(repetition);
+    }
+
+    //Same as get_game_state but with the repetition rule of is_search_repetition
+    pub fn get_search_state(&mut self, root_depth: u32, twofold: bool) -> GameState {
+        let repetition = self.is_search_repetition(root_depth, twofold);
+
+        return self.get_state(repetition);
+    }
+
+    //[TODO] insuff material
+    fn get_state(&mut self, repetition: bool) -> GameState {
         if self.get_legal_moves().len() == 0 {
             if self.board.in_check() {
                 if self.is_whites_turn() {
@@ -222,7 +292,7 @@ impl  Game {
             }
         }   
 
-        if self.second_board_history.contains(&self.board.get_zoberist_hash()) {
+        if repetition {
             return GameState::Repetition;
         }
 
@@ -0,0 +1,113 @@
+use crate::game::GameState;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Outcome {
+    WhiteWins, BlackWins, Draw
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ResultReason {
+    Checkmate, Stalemate, FiftyMove, Repetition, InsuffMaterial, Adjudication, TimeForfeit, IllegalMove
+}
+
+impl ResultReason {
+    pub fn to_string(&self) -> &str {
+        return match *self {
+            ResultReason::Checkmate => "Checkmate",
+            ResultReason::Stalemate => "Stalemate",
+            ResultReason::FiftyMove => "Fifty move rule",
+            ResultReason::Repetition => "Repetition",
+            ResultReason::InsuffMaterial => "Insufficient material",
+            ResultReason::Adjudication => "Adjudication",
+            ResultReason::TimeForfeit => "Time forfeit",
+            ResultReason::IllegalMove => "Illegal move",
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    pub reason: ResultReason,
+}
+
+impl GameResult {
+    pub fn new(outcome: Outcome, reason: ResultReason) -> GameResult {
+        return GameResult { outcome, reason };
+    }
+
+    pub fn win_for(white: bool, reason: ResultReason) -> GameResult {
+        return GameResult::new(if white { Outcome::WhiteWins } else { Outcome::BlackWins }, reason);
+    }
+
+    pub fn loss_for(white: bool, reason: ResultReason) -> GameResult {
+        return GameResult::win_for(!white, reason);
+    }
+
+    pub fn draw(reason: ResultReason) -> GameResult {
+        return GameResult::new(Outcome::Draw, reason);
+    }
+
+    //None while the game is still running
+    pub fn from_game_state(state: GameState) -> Option<GameResult> {
+        return match state {
+            GameState::Undecided => None,
+            //The state names the side that got mated
+            GameState::WhiteCheckmate => Some(GameResult::win_for(false, ResultReason::Checkmate)),
+            GameState::BlackCheckmate => Some(GameResult::win_for(true, ResultReason::Checkmate)),
+            GameState::Stalemate => Some(GameResult::draw(ResultReason::Stalemate)),
+            GameState::FiftyMove => Some(GameResult::draw(ResultReason::FiftyMove)),
+            GameState::Repetition => Some(GameResult::draw(ResultReason::Repetition)),
+            GameState::InsuffMaterial => Some(GameResult::draw(ResultReason::InsuffMaterial)),
+        };
+    }
+
+    pub fn is_draw(&self) -> bool {
+        return self.outcome == Outcome::Draw;
+    }
+
+    //Winner as side, None for a draw
+    pub fn get_winner(&self) -> Option<bool> {
+        return match self.outcome {
+            Outcome::WhiteWins => Some(true),
+            Outcome::BlackWins => Some(false),
+            Outcome::Draw => None,
+        };
+    }
+
+    pub fn is_win_for(&self, white: bool) -> bool {
+        return self.get_winner() == Some(white);
+    }
+
+    pub fn is_loss_for(&self, white: bool) -> bool {
+        return self.get_winner() == Some(!white);
+    }
+
+    pub fn get_pgn_result(&self) -> &str {
+        return match self.outcome {
+            Outcome::WhiteWins => "1-0",
+            Outcome::BlackWins => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        };
+    }
+
+    //Value for the pgn Termination tag
+    pub fn get_termination(&self) -> &str {
+        return match self.reason {
+            ResultReason::Adjudication => "adjudication",
+            ResultReason::TimeForfeit => "time forfeit",
+            ResultReason::IllegalMove => "rules infraction",
+            _ => "normal",
+        };
+    }
+
+    pub fn to_string(&self) -> String {
+        let outcome = match self.outcome {
+            Outcome::WhiteWins => "White wins",
+            Outcome::BlackWins => "Black wins",
+            Outcome::Draw => "Draw",
+        };
+
+        return format!("{}: {}", outcome, self.reason.to_string());
+    }
+}
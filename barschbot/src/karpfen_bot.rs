@@ -1,59 +1,109 @@
-use std::{cmp::min, collections::{btree_map::Values, HashMap}};
+use std::{cmp::min, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread, time::Instant};
 
 use arrayvec::ArrayVec;
 
-use crate::{bb_settings, bit_board::BitBoard, chess_move::{self, ChessMove}, colored_piece_type::ColoredPieceType, endgame_table::{self, EndgameTable}, evaluation, game::{Game, GameState}, kb_settings::{self, KBSettings}, opening_book::OpeningBook, piece_type::PieceType, search_stats::SearchStats, square::{self, Square}};
-
-#[derive(PartialEq, Eq, Clone, Copy)]
-enum NodeType {
-    Exact,
-    LowerBound,
-    UpperBound,
-    Unknown,
-}
+use crate::{bb_settings, bit_board::BitBoard, chess_move::{self, ChessMove}, colored_piece_type::ColoredPieceType, endgame_table::{self, EndgameTable}, evaluation, game::{Game, GameState}, kb_settings::{self, KBSettings}, move_picker::{MovePicker, PickerStage}, opening_book::OpeningBook, piece_type::PieceType, search_info::{InfoScore, SearchInfo, SearchReporter, SilentReporter}, search_stats::SearchStats, square::{self, Square}, time_manager::{SearchHandle, SearchLimits, TimeManager}, transposition_table::{self, NodeType, TranspositionTable}};
 
+//One MultiPV line, score from the side to move
 #[derive(Clone)]
-struct TTEntry {
-    depth: u8,
-    score: i32,
-    best_move: ChessMove,
-    node_type: NodeType,
+pub struct RootLine {
+    pub root_move: ChessMove,
+    pub score: i32,
+    pub pv: Vec<ChessMove>,
 }
 
 pub struct KarpfenBot {
     stats: SearchStats,
     quiet_move_history: [[u64; 64]; 64],
     killer_moves: [ChessMove; 256],
-    transposition_table: HashMap<u64, TTEntry>,
+    //Quiet reply that last refuted a move, indexed by its start and target square
+    counter_moves: [[ChessMove; 64]; 64],
+    //Shared with the helper threads
+    transposition_table: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    //Lazy SMP helpers, thread_id 0 is the bot itself and decides the move
+    helpers: Vec<KarpfenBot>,
+    thread_id: usize,
+    //Only used by the main thread, helpers run until stop is set
+    time_manager: TimeManager,
+    //Stop and ponderhit from outside, e.g. the UCI input thread
+    search_handle: SearchHandle,
     settings: KBSettings,
     root_move: ChessMove,
+    //Triangular table, pv_table[ply] holds the line found below that ply
+    pv_table: [[ChessMove; MAX_PLY]; MAX_PLY],
+    pv_length: [usize; MAX_PLY],
+    //Principal variation of the last finished iteration
+    pv: Vec<ChessMove>,
+    //Best lines of the last finished iteration, sorted by score
+    root_lines: Vec<RootLine>,
+    //Root moves skipped by the search, the lines found before in the current MultiPV iteration
+    excluded_root_moves: ArrayVec<ChessMove, 200>,
+    //Base late move reduction by depth left and move number
+    lmr_table: [[u8; LMR_TABLE_SIZE]; LMR_TABLE_SIZE],
+    //Result of the last finished iteration, depth 0 for book and forced moves
+    last_score: i32,
+    last_depth: u8,
 }
 
-const CHECKMATE_VALUE: i32 = 100_000;
+pub const CHECKMATE_VALUE: i32 = 100_000;
 const DO_PRINT: bool = false;
 
+const MAX_SEARCH_DEPTH: u8 = 64;
+const MAX_PLY: usize = 130;
+const HELPER_STACK_SIZE: usize = 16 * 1024 * 1024;
+const LMR_TABLE_SIZE: usize = 64;
+//Quiets whose history is worth this many cutoffs at the current depth get reduced one ply less
+const HISTORY_REDUCTION_FACTOR: u64 = 16;
+
+//Scores beyond this are mates, pruning margins make no sense for them
+const MATE_SCORE_BOUND: i32 = CHECKMATE_VALUE - 1000;
+//Forward pruning margins in eval units, a pawn is worth 1000 to 2000
+const RFP_MAX_DEPTH: u8 = 6;
+const RFP_MARGIN: i32 = 900;
+const RAZOR_MAX_DEPTH: u8 = 3;
+const RAZOR_MARGIN: i32 = 2500;
+const FUTILITY_MAX_DEPTH: u8 = 3;
+const FUTILITY_MARGIN: i32 = 1500;
+const LMP_MAX_DEPTH: u8 = 4;
+//Every multiple of this the eval is above beta takes one more ply off the null move search
+const NMP_EVAL_DIVISOR: i32 = 2000;
+
 impl KarpfenBot {
     pub fn new() -> KarpfenBot {
-        return KarpfenBot {
-            stats: SearchStats::new(),
-            quiet_move_history: [[0; 64]; 64],
-            killer_moves: [chess_move::NULL_MOVE; 256],
-            transposition_table: HashMap::new(),
-            settings: kb_settings::STANDARD_KB_SETTINGS,
-            root_move: chess_move::NULL_MOVE,
-        };
+        return KarpfenBot::with_settings(kb_settings::STANDARD_KB_SETTINGS);
     }
 
     pub fn with_settings(settings: KBSettings) -> KarpfenBot {
+        let table = Arc::new(TranspositionTable::new(transposition_table::DEFAULT_HASH_SIZE));
+
+        return KarpfenBot::from_parts(settings, table, Arc::new(AtomicBool::new(false)), 0);
+    }
+
+    fn from_parts(settings: KBSettings, transposition_table: Arc<TranspositionTable>, stop: Arc<AtomicBool>, thread_id: usize) -> KarpfenBot {
         return KarpfenBot {
             stats: SearchStats::new(),
             quiet_move_history: [[0; 64]; 64],
             killer_moves: [chess_move::NULL_MOVE; 256],
-            transposition_table: HashMap::new(),
+            counter_moves: [[chess_move::NULL_MOVE; 64]; 64],
+            transposition_table,
+            stop,
+            helpers: Vec::new(),
+            thread_id,
+            time_manager: TimeManager::new(&SearchLimits::new(), true),
+            search_handle: SearchHandle::new(),
             settings: settings,
             root_move: chess_move::NULL_MOVE,
+            pv_table: [[chess_move::NULL_MOVE; MAX_PLY]; MAX_PLY],
+            pv_length: [0; MAX_PLY],
+            pv: Vec::new(),
+            root_lines: Vec::new(),
+            excluded_root_moves: ArrayVec::new(),
+            lmr_table: get_lmr_table(),
+            last_score: 0,
+            last_depth: 0,
         };
-    }   
+    }
 
     pub fn reset(&mut self) {
         for i in 0..64 {
@@ -61,11 +111,113 @@ impl KarpfenBot {
         }
         self.transposition_table.clear();
         self.killer_moves.fill(chess_move::NULL_MOVE);
+        self.counter_moves = [[chess_move::NULL_MOVE; 64]; 64];
         self.root_move = chess_move::NULL_MOVE;
         self.stats.reset();
+        self.helpers.clear();
+    }
+
+    pub fn get_settings(&self) -> KBSettings {
+        return self.settings.clone();
+    }
+
+    pub fn set_settings(&mut self, settings: KBSettings) {
+        self.settings = settings;
+    }
+
+    pub fn get_last_score(&self) -> i32 {
+        return self.last_score;
+    }
+
+    pub fn get_last_depth(&self) -> u8 {
+        return self.last_depth;
+    }
+
+    //Empty for book and forced moves
+    pub fn get_pv(&self) -> &[ChessMove] {
+        return &self.pv;
+    }
+
+    //KBSettings::multi_pv lines at most, empty for book and forced moves
+    pub fn get_root_lines(&self) -> &[RootLine] {
+        return &self.root_lines;
+    }
+
+    //The line_count best root moves, ranked. Ignores the opening book
+    pub fn analyse(&mut self, game: &mut Game, endgame_table: &EndgameTable, limits: &SearchLimits, line_count: usize, reporter: &mut dyn SearchReporter) -> Vec<RootLine> {
+        let base_settings = self.settings.clone();
+        self.settings.multi_pv = line_count.max(1);
+
+        self.get_best_move_with_limits(game, &OpeningBook::new(), endgame_table, limits, reporter);
+        self.settings = base_settings;
+
+        if self.root_lines.len() == 0 {
+            return game.get_legal_moves().iter().take(1).map(|m| RootLine { root_move: *m, score: 0, pv: vec![*m] }).collect();
+        }
+
+        return self.root_lines.clone();
+    }
+
+    //Call reset on it before starting a search from another thread, with ponder set for "go ponder"
+    pub fn get_search_handle(&self) -> SearchHandle {
+        return self.search_handle.clone();
+    }
+
+    //Expected reply to best_move, taken from the PV or else from the transposition table
+    pub fn get_ponder_move(&self, game: &mut Game, best_move: ChessMove) -> ChessMove {
+        if best_move.is_null_move() {
+            return chess_move::NULL_MOVE;
+        }
+
+        if self.pv.len() >= 2 && self.pv[0] == best_move {
+            return self.pv[1];
+        }
+
+        game.make_move(best_move);
+
+        let mut ponder_move = chess_move::NULL_MOVE;
+        if let Some(entry) = self.transposition_table.probe(&game.get_board(), 1) {
+            if game.get_legal_moves().contains(&entry.best_move) {
+                ponder_move = entry.best_move;
+            }
+        }
+
+        game.undo_move();
+
+        return ponder_move;
+    }
+
+    pub fn get_stats(&self) -> &SearchStats {
+        return &self.stats;
+    }
+
+    //Reallocates the table, everything stored so far is lost
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.transposition_table = Arc::new(TranspositionTable::new(size_mb));
+        self.helpers.clear();
+    }
+
+    pub fn get_hash_size(&self) -> usize {
+        return self.transposition_table.get_size_mb();
+    }
+
+    pub fn get_hashfull(&self) -> u32 {
+        return self.transposition_table.get_hashfull();
     }
 
+    //Searches up to KBSettings::max_depth and for at least KBSettings::min_search_time
     pub fn get_best_move(&mut self, game: &mut Game, opening_book: &OpeningBook, endgame_table: &EndgameTable) -> ChessMove {
+        let limits = SearchLimits::from_depth(self.settings.max_depth, self.settings.min_search_time);
+
+        return self.get_best_move_with_limits(game, opening_book, endgame_table, &limits, &mut SilentReporter);
+    }
+
+    pub fn get_best_move_with_limits(&mut self, game: &mut Game, opening_book: &OpeningBook, endgame_table: &EndgameTable, limits: &SearchLimits, reporter: &mut dyn SearchReporter) -> ChessMove {
+        self.last_score = 0;
+        self.last_depth = 0;
+        self.pv.clear();
+        self.root_lines.clear();
+
         let om = opening_book.get_move(game.get_board().get_zoberist_hash());
 
         if om != chess_move::NULL_MOVE {
@@ -73,14 +225,53 @@ impl KarpfenBot {
             return om;
         }
 
-        self.root_move == chess_move::NULL_MOVE;
-
         let moves = game.get_legal_moves();
         if moves.len() == 1 {
             return moves[0];
         }
 
-        let mut score = 0;
+        self.time_manager = TimeManager::new(limits, game.is_whites_turn()).with_handle(self.search_handle.clone(), limits.ponder);
+        self.transposition_table.new_search();
+        self.prepare_search();
+        //A threaded search ends with stop set
+        self.stop.store(false, Ordering::Relaxed);
+
+        if self.settings.thread_count <= 1 {
+            self.iterative_deepening(game, endgame_table, reporter);
+            return self.root_move;
+        }
+
+        self.update_helpers();
+
+        let mut helpers = std::mem::take(&mut self.helpers);
+
+        thread::scope(|scope| {
+            for helper in helpers.iter_mut() {
+                let mut helper_game = game.clone();
+
+                thread::Builder::new().stack_size(HELPER_STACK_SIZE).spawn_scoped(scope, move || {
+                    helper.prepare_search();
+                    helper.iterative_deepening(&mut helper_game, endgame_table, &mut SilentReporter);
+                }).unwrap();
+            }
+
+            self.iterative_deepening(game, endgame_table, reporter);
+            self.stop.store(true, Ordering::Relaxed);
+        });
+
+        for helper in &helpers {
+            self.stats.merge(&helper.stats);
+        }
+
+        self.helpers = helpers;
+
+        return self.root_move;
+    }
+
+    fn prepare_search(&mut self) {
+        self.root_move = chess_move::NULL_MOVE;
+        self.stats.reset();
+
         for i in 0..self.quiet_move_history.len() {
             for j in 0..self.quiet_move_history[i].len() {
                 self.quiet_move_history[i][j] /= 8;
@@ -90,65 +281,174 @@ impl KarpfenBot {
         for i in 0..self.killer_moves.len() {
             self.killer_moves[i] = chess_move::NULL_MOVE;
         }
+    }
+
+    //One helper per extra thread, they keep their history tables between moves
+    fn update_helpers(&mut self) {
+        let helper_count = self.settings.thread_count - 1;
+        self.helpers.truncate(helper_count);
+
+        while self.helpers.len() < helper_count {
+            let thread_id = self.helpers.len() + 1;
+            self.helpers.push(KarpfenBot::from_parts(self.settings.clone(), self.transposition_table.clone(), self.stop.clone(), thread_id));
+        }
+
+        for helper in self.helpers.iter_mut() {
+            helper.settings = self.settings.clone();
+        }
+    }
 
-        let start = std::time::Instant::now();
-        let mut max_depth = 1;
+    fn is_stopped(&self) -> bool {
+        return self.stop.load(Ordering::Relaxed);
+    }
+
+    //The main thread asks the time manager before every iteration, helpers keep going until the main thread sets stop.
+    //Leaves the best move of the last finished iteration in root_move
+    fn iterative_deepening(&mut self, game: &mut Game, endgame_table: &EndgameTable, reporter: &mut dyn SearchReporter) {
+        let print = DO_PRINT && self.thread_id == 0;
+        let start = Instant::now();
+
+        //Helpers only help with the best line
+        let root_move_count = game.get_legal_moves().len();
+        let line_count = if self.thread_id == 0 { self.settings.multi_pv.clamp(1, root_move_count.max(1)) } else { 1 };
+        //Aspiration window centers, by rank
+        let mut scores = vec![0; line_count];
+
+        //Odd helpers start one ply deeper, so the threads are spread over two depths
+        let mut max_depth = 1 + (self.thread_id % 2) as u8;
         loop {
-            let mut window = 220;
-            
-            if DO_PRINT {
+            if print {
                 println!("Depth: {}", max_depth);
             }
-            loop {
-                let alpha = score - window;
-                let beta = score + window;
-    
-                if DO_PRINT {
-                    println!("\t[{}, {}]", alpha, beta);
-                }
 
-                score = self.search(0, max_depth, alpha, beta, true, game, endgame_table);
-                
-                window *= 2;
+            let mut lines = Vec::new();
+            self.excluded_root_moves.clear();
 
-                if score.abs() > CHECKMATE_VALUE - 100 {
-                    if DO_PRINT {
-                        println!("Checkmate found   ");
+            for pv_index in 0..line_count {
+                let mut score = scores[pv_index];
+                let mut window = 220;
+
+                loop {
+                    let alpha = score - window;
+                    let beta = score + window;
+        
+                    if print {
+                        println!("\t[{}, {}]", alpha, beta);
                     }
-                    break;
-                }
 
-                if alpha < score && score < beta {
-                    break;
+                    score = self.search(0, max_depth, alpha, beta, true, game, endgame_table);
+
+                    //The unfinished iteration is thrown away, unless not even the first one finished
+                    if self.is_stopped() {
+                        self.excluded_root_moves.clear();
+
+                        if self.root_lines.len() > 0 {
+                            self.root_move = self.root_lines[0].root_move;
+                        }
+                        else if self.root_move.is_null_move() {
+                            self.root_move = game.get_legal_moves()[0];
+                        }
+
+                        return;
+                    }
+                    
+                    window *= 2;
+
+                    if score.abs() > CHECKMATE_VALUE - 100 {
+                        if print {
+                            println!("Checkmate found   ");
+                        }
+                        break;
+                    }
+
+                    if alpha < score && score < beta {
+                        break;
+                    }
                 }
+
+                lines.push(RootLine { root_move: self.root_move, score, pv: self.get_root_pv() });
+                self.excluded_root_moves.push(self.root_move);
             }
 
+            self.excluded_root_moves.clear();
+            lines.sort_by_key(|line| -line.score);
+
+            scores = lines.iter().map(|line| line.score).collect();
+            self.root_lines = lines;
+            self.root_move = self.root_lines[0].root_move;
+            self.pv = self.root_lines[0].pv.clone();
+
+            let score = self.root_lines[0].score;
+            self.last_score = score;
+            self.last_depth = max_depth;
 
             let elapsed = start.elapsed().as_millis();
 
-            if DO_PRINT {
-                println!("Elapsed time: {}ms", elapsed);
+            if self.thread_id == 0 {
+                for (i, line) in self.root_lines.iter().enumerate() {
+                    reporter.report(&SearchInfo {
+                        depth: max_depth,
+                        seldepth: self.stats.seldepth,
+                        multipv: i + 1,
+                        score: InfoScore::from_score(line.score),
+                        nodes: self.stats.nodes,
+                        time: elapsed as u64,
+                        hashfull: Some(self.get_hashfull()),
+                        pv: line.pv.clone(),
+                    });
+                }
+            }
+
+            if print {
+                println!("Elapsed time: {}ms Hashfull: {}", elapsed, self.get_hashfull());
+                self.stats.print();
             }
 
-            if max_depth >= self.settings.max_depth && elapsed as u64 >= self.settings.min_search_time {
+            let mate_plies = if score > CHECKMATE_VALUE - 1000 { Some((CHECKMATE_VALUE - score) as u32) } else { None };
+
+            if self.thread_id == 0 && !self.time_manager.should_start_iteration(max_depth, mate_plies) {
+                break;
+            }
+
+            if max_depth >= MAX_SEARCH_DEPTH {
                 break;
             }
 
             max_depth += 1;
         }
+    }
 
-        return self.root_move;
+    fn get_root_pv(&self) -> Vec<ChessMove> {
+        let pv = self.pv_table[0][..self.pv_length[0]].to_vec();
+
+        //A root fail low leaves no line, the mate break above can end on one
+        if pv.len() == 0 || pv[0] != self.root_move {
+            return vec![self.root_move];
+        }
+
+        return pv;
     }
 
-    pub fn search(&mut self, ply: i8, depth_left: u8, mut alpha: i32, beta: i32, null_allowed: bool, game: &mut Game, endgame_table: &EndgameTable) -> i32 {
-        let gs = game.get_game_state();
+    fn count_node(&mut self, ply: i8) {
+        self.stats.nodes += 1;
+        self.stats.seldepth = self.stats.seldepth.max(ply as u8 + 1);
+        self.pv_length[ply as usize] = ply as usize;
+
+        if self.thread_id == 0 && self.time_manager.check(self.stats.nodes) {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+    }
 
-        if gs.is_draw() {
+    pub fn search(&mut self, ply: i8, depth_left: u8, mut alpha: i32, beta: i32, null_allowed: bool, game: &mut Game, endgame_table: &EndgameTable) -> i32 {
+        if self.is_stopped() {
             return 0;
         }
 
-        if gs.is_checkmate() {
-            return -CHECKMATE_VALUE + ply as i32;
+        self.count_node(ply);
+
+        //Mate and stalemate are only known after the move loop. The root has to return a move even in a drawn position
+        if ply > 0 && game.is_search_draw(game.move_depth() - ply as u32, self.settings.twofold_repetition) {
+            return 0;
         }
 
         let pair = get_relative_endgame_eval(&game.get_board(), endgame_table);
@@ -163,20 +463,9 @@ impl KarpfenBot {
         let min_window_search = alpha == beta - 1;
 
         let in_check = game.get_board().in_check();
-        let zkey = game.get_board().get_zoberist_hash();
         let is_pawn_endgame = game.get_board().is_only_pawns();
 
-        let tt_entry = if self.transposition_table.contains_key(&zkey) {
-            self.transposition_table[&zkey].clone()
-        }
-        else {
-            TTEntry {
-                depth: 0,
-                score: 0,
-                best_move: chess_move::NULL_MOVE,
-                node_type: NodeType::Unknown,
-            }
-        };
+        let tt_entry = self.transposition_table.probe(&game.get_board(), ply).unwrap_or(transposition_table::EMPTY_ENTRY);
 
         //When doing a min_window_search it is sufficient to know whether we fail high or low
         //alpha < score < beta
@@ -202,66 +491,131 @@ impl KarpfenBot {
             local_score = tt_entry.score;
         }
 
-        //Null move pruning
-        if null_allowed && local_score >= beta && depth_left >= 3 && !in_check && !game.get_board().is_only_pawns() {
-            game.make_move(chess_move::NULL_MOVE);
-            
-            let r = -self.search(ply + 1, depth_left - 3, -beta, -beta + 1, false, game, endgame_table);
+        let pv_node = !min_window_search;
+        //Static eval, sharpened by the TT bound. Only trusted for pruning outside of check and away from mates
+        let eval = local_score;
+        let can_prune = ply > 0 && !pv_node && !in_check;
 
-            game.undo_move();
+        //Reverse futility pruning
+        if self.settings.reverse_futility_pruning && can_prune && depth_left <= RFP_MAX_DEPTH && beta.abs() < MATE_SCORE_BOUND
+            && eval - RFP_MARGIN * depth_left as i32 >= beta {
+            self.stats.reverse_futility_prunes += 1;
+            return eval;
+        }
 
-            if r >= beta {
-                return beta;
+        //Razoring
+        if self.settings.razoring && can_prune && depth_left <= RAZOR_MAX_DEPTH && alpha.abs() < MATE_SCORE_BOUND
+            && eval + RAZOR_MARGIN * (depth_left as i32) < alpha {
+            let score = self.quiescence_search(ply, alpha, beta, game, endgame_table);
+
+            if score <= alpha {
+                self.stats.razor_prunes += 1;
+                return score;
             }
         }
 
-        let mut moves = game.get_legal_moves();
-        //[Todo] try no caching
-        moves.sort_by_cached_key(|cm| {
-            if *cm == tt_entry.best_move {
-                return 1_u64 << 60;
-            }
+        //Null move pruning
+        if self.settings.null_move_pruning && null_allowed && ply > 0 && local_score >= beta && depth_left >= 3 && !in_check
+            && beta.abs() < MATE_SCORE_BOUND && (!is_pawn_endgame || self.settings.null_move_verification) {
+            let reduction = if self.settings.adaptive_null_move_reduction { get_null_move_reduction(depth_left, eval, beta) } else { 3 };
+            let null_depth = depth_left.saturating_sub(reduction);
 
-            if cm.is_direct_capture() {
-                return (1_u64 << 50) * piece_value(cm.capture_piece_type) - piece_value(cm.move_piece_type);
-            }
+            game.make_move(chess_move::NULL_MOVE);
+            
+            let r = -self.search(ply + 1, null_depth, -beta, -beta + 1, false, game, endgame_table);
 
-            if cm.is_promotion() || cm.is_en_passant() {
-                return 1_u64 << 45;
-            }
+            game.undo_move();
 
-            if *cm == self.killer_moves[ply as usize] {
-                return 1_u64 << 40;
+            if r >= beta {
+                //Zugzwang is common without pieces, the side to move has to hold beta without passing too
+                if is_pawn_endgame && self.search(ply, null_depth, beta - 1, beta, false, game, endgame_table) < beta {
+                    self.stats.null_move_refutations += 1;
+                }
+                else {
+                    self.stats.null_move_prunes += 1;
+                    return beta;
+                }
             }
+        }
 
-            return self.quiet_move_history[cm.start_square as usize][cm.target_square as usize];
+        let board = game.get_board();
+        let previous_move = if game.move_depth() > 0 { game.last_move() } else { chess_move::NULL_MOVE };
+        let counter_move = if previous_move.is_null_move() { chess_move::NULL_MOVE } 
+            else { self.counter_moves[previous_move.start_square as usize][previous_move.target_square as usize] };
 
-            fn piece_value (cpt: ColoredPieceType) -> u64 {
-                const VALUES: [u64; 6] = [1, 3, 3, 5, 11, 100];
-                return VALUES[PieceType::from_cpt(cpt) as usize];
-            }
-        });
+        let mut picker = MovePicker::new(&board, tt_entry.best_move, self.killer_moves[ply as usize], counter_move);
         
         let mut best_score = -2_000_000_000;
         let mut best_move = chess_move::NULL_MOVE;        
 
         let mut node_type = NodeType::UpperBound;
         let mut quiets_evaluated = ArrayVec::<ChessMove, 200>::new();
+        let mut legal_moves = 0;
 
-        for m in moves.iter().rev() {
-            let m = *m;
+        while let Some((m, stage)) = picker.next(&self.quiet_move_history) {
+            if !board.is_legal(m) || (ply == 0 && self.excluded_root_moves.contains(&m)) {
+                continue;
+            }
+
+            legal_moves += 1;
             game.make_move(m);
 
             let m_in_check = game.get_board().in_check();
             let is_quiet = !m.is_capture() 
                 && !m.is_promotion();
 
+            //Quiets near the leaves that neither give check nor can reach alpha, once a move has been searched
+            if can_prune && is_quiet && !m_in_check && best_score > -MATE_SCORE_BOUND {
+                if self.settings.late_move_pruning && depth_left <= LMP_MAX_DEPTH && quiets_evaluated.len() >= get_late_move_count(depth_left) {
+                    self.stats.late_move_prunes += 1;
+                    game.undo_move();
+                    continue;
+                }
+
+                if self.settings.futility_pruning && depth_left <= FUTILITY_MAX_DEPTH && eval + FUTILITY_MARGIN * depth_left as i32 <= alpha {
+                    self.stats.futility_prunes += 1;
+                    game.undo_move();
+                    continue;
+                }
+            }
+
             let reduction = if m_in_check { 0 } else { 1 };
+            let new_depth = depth_left - reduction;
 
-            local_score = -self.search(ply + 1, depth_left - reduction, -beta, -alpha, true, game, endgame_table);
-         
+            if legal_moves == 1 {
+                local_score = -self.search(ply + 1, new_depth, -beta, -alpha, true, game, endgame_table);
+            }
+            else {
+                let late_move_reduction = if self.settings.late_move_reductions && is_quiet && !in_check && !m_in_check && depth_left >= 3 {
+                    self.get_late_move_reduction(depth_left, legal_moves, m, stage, !min_window_search)
+                } else { 0 };
+
+                //Without PVS the reduced search still gets the full window
+                let search_beta = if self.settings.principal_variation_search { alpha + 1 } else { beta };
+
+                if late_move_reduction > 0 {
+                    self.stats.late_move_reductions += 1;
+                }
+
+                local_score = -self.search(ply + 1, new_depth - late_move_reduction, -search_beta, -alpha, true, game, endgame_table);
+
+                if late_move_reduction > 0 && local_score > alpha {
+                    self.stats.re_searches += 1;
+                    local_score = -self.search(ply + 1, new_depth, -search_beta, -alpha, true, game, endgame_table);
+                }
+
+                if search_beta < beta && local_score > alpha && local_score < beta {
+                    self.stats.re_searches += 1;
+                    local_score = -self.search(ply + 1, new_depth, -beta, -alpha, true, game, endgame_table);
+                }
+            }
+        
             game.undo_move();
 
+            if self.is_stopped() {
+                return 0;
+            }
+
             if local_score > best_score {
                 best_score = local_score;
                 
@@ -269,7 +623,7 @@ impl KarpfenBot {
                     self.root_move = m;
 
                     if DO_PRINT {
-                        println!("\t\tBest move: {} Score: {}", m.get_board_name(&game.get_board()), best_score);
+                        println!("\t\tBest move: {} Score: {}", m.get_san(&game.get_board()), best_score);
                     }
                 }
 
@@ -279,6 +633,7 @@ impl KarpfenBot {
                     alpha = local_score;
 
                     node_type = NodeType::Exact;
+                    self.update_pv(ply as usize, m);
 
                     if local_score >= beta {
                         node_type = NodeType::LowerBound;
@@ -293,7 +648,13 @@ impl KarpfenBot {
                             }
 
                             self.killer_moves[ply as usize] = m;
+
+                            if !previous_move.is_null_move() {
+                                self.counter_moves[previous_move.start_square as usize][previous_move.target_square as usize] = m;
+                            }
                         }
+
+                        self.stats.stage_cutoffs[stage as usize] += 1;
                         break;
                     }
                 }
@@ -304,21 +665,55 @@ impl KarpfenBot {
             }
         }
 
-        if depth_left >= tt_entry.depth && (node_type == NodeType::Exact ||
-            tt_entry.node_type == NodeType::Unknown || 
-            tt_entry.node_type == node_type) {
-            
-            self.transposition_table.insert(zkey, TTEntry {
-                depth: depth_left,
-                score: best_score,
-                best_move: best_move,
-                node_type: node_type,
-            });
+        if legal_moves == 0 {
+            return if in_check { -CHECKMATE_VALUE + ply as i32 } else { 0 };
+        }
+
+        //With excluded moves the root result is not the one of the position
+        if ply > 0 || self.excluded_root_moves.len() == 0 {
+            self.transposition_table.store(&board, ply, depth_left, best_score, best_move, node_type);
         }
 
         return best_score;
     }
 
+    //Never drops the search below depth 1, so the reduced move still gets a full ply before quiescence
+    fn get_late_move_reduction(&self, depth_left: u8, move_number: usize, m: ChessMove, stage: PickerStage, pv_node: bool) -> u8 {
+        let mut reduction = self.lmr_table[(depth_left as usize).min(LMR_TABLE_SIZE - 1)][move_number.min(LMR_TABLE_SIZE - 1)] as i32;
+
+        if pv_node {
+            reduction -= 1;
+        }
+
+        if stage == PickerStage::Killer || stage == PickerStage::CounterMove {
+            reduction -= 1;
+        }
+
+        //Every beta cutoff adds depth² to the history
+        let history = self.quiet_move_history[m.start_square as usize][m.target_square as usize];
+        let cutoff_value = depth_left as u64 * depth_left as u64;
+
+        if history == 0 {
+            reduction += 1;
+        }
+        else if history >= cutoff_value * HISTORY_REDUCTION_FACTOR {
+            reduction -= 1;
+        }
+
+        return reduction.clamp(0, depth_left as i32 - 2) as u8;
+    }
+
+    fn update_pv(&mut self, ply: usize, m: ChessMove) {
+        let child_length = self.pv_length[ply + 1].max(ply + 1);
+
+        self.pv_table[ply][ply] = m;
+        for i in (ply + 1)..child_length {
+            self.pv_table[ply][i] = self.pv_table[ply + 1][i];
+        }
+
+        self.pv_length[ply] = child_length;
+    }
+
     /* 
     pub fn in_check_search(&mut self, ply: i8, depth_left: i8, mut alpha: i32, game: &mut Game) {
         
@@ -326,14 +721,15 @@ impl KarpfenBot {
     */
 
     pub fn quiescence_search(&mut self, ply: i8, mut alpha: i32, beta: i32, game: &mut Game, endgame_table: &EndgameTable) -> i32 {
-        let gs = game.get_game_state();
-
-        if gs.is_draw() {
+        if self.is_stopped() {
             return 0;
         }
 
-        if gs.is_checkmate() {
-            return -CHECKMATE_VALUE + ply as i32;
+        self.count_node(ply);
+        self.stats.qs += 1;
+
+        if game.is_search_draw(game.move_depth() - ply as u32, self.settings.twofold_repetition) {
+            return 0;
         }
 
         let pair = get_relative_endgame_eval(&game.get_board(), endgame_table);
@@ -341,21 +737,16 @@ impl KarpfenBot {
             return pair.0;
         }
 
-        let in_check = game.get_board().in_check();
-        let zkey = game.get_board().get_zoberist_hash();
-        let is_pawn_endgame = game.get_board().is_only_pawns();
+        let board = game.get_board();
+        let in_check = board.in_check();
+        let is_pawn_endgame = board.is_only_pawns();
 
-        let tt_entry = if self.transposition_table.contains_key(&zkey) {
-            self.transposition_table[&zkey].clone()
+        //Stand pat is allowed in check as well, so mates have to be found before it
+        if in_check && board.get_legal_moves().len() == 0 {
+            return -CHECKMATE_VALUE + ply as i32;
         }
-        else {
-            TTEntry {
-                depth: 0,
-                score: 0,
-                best_move: chess_move::NULL_MOVE,
-                node_type: NodeType::Unknown,
-            }
-        };
+
+        let tt_entry = self.transposition_table.probe(&game.get_board(), ply).unwrap_or(transposition_table::EMPTY_ENTRY);
 
         //alpha < score < beta
         if alpha == beta -1 &&
@@ -388,36 +779,19 @@ impl KarpfenBot {
             alpha = local_score;
         }
 
-        let mut moves = game.get_legal_moves();
-
-        //[Todo] try no caching
-        moves.sort_by_cached_key(|cm| {
-            if cm == &tt_entry.best_move {
-                return 1_u64 << 60;
-            }
-
-            if cm.is_direct_capture() {
-                return (1_u64 << 50) * 
-                    (piece_value(cm.capture_piece_type) - piece_value(cm.move_piece_type));
-            }
-
-            if cm == &self.killer_moves[ply as usize] {
-                return 1_u64 << 49;
-            }
-
-            return self.quiet_move_history[cm.start_square as usize][cm.target_square as usize];
-
-            fn piece_value (cpt: ColoredPieceType) -> u64 {
-                return PieceType::from_cpt(cpt) as u64;
-            }
-        });
+        let mut picker = MovePicker::new_quiescence(&board, tt_entry.best_move, in_check);
         
         let mut best_score = local_score;         //Quiesence only
         let mut best_move = chess_move::NULL_MOVE;        
 
-        for m in moves {
+        while let Some((m, stage)) = picker.next(&self.quiet_move_history) {
+            //Only captures that lose material are left, those are pruned
+            if stage == PickerStage::BadCaptures && !in_check {
+                break;
+            }
+
             //Quiesence only
-            if !m.is_capture() && !in_check {
+            if (!m.is_capture() && !in_check) || !board.is_legal(m) {
                 continue;
             }
 
@@ -427,6 +801,10 @@ impl KarpfenBot {
             
             game.undo_move();
 
+            if self.is_stopped() {
+                return 0;
+            }
+
             if local_score > best_score {
                 best_score = local_score;
                 
@@ -436,7 +814,7 @@ impl KarpfenBot {
                     alpha = local_score;
 
                     if local_score >= beta {
-
+                        self.stats.stage_cutoffs[stage as usize] += 1;
                         break;
                     }
                 }
@@ -447,6 +825,31 @@ impl KarpfenBot {
     }
 }
 
+//Depth taken off the null move search, including the ply of the null move itself
+fn get_null_move_reduction(depth_left: u8, eval: i32, beta: i32) -> u8 {
+    let eval_bonus = ((eval - beta) / NMP_EVAL_DIVISOR).clamp(0, 2) as u8;
+
+    return 3 + depth_left / 6 + eval_bonus;
+}
+
+//Quiet moves searched before late move pruning starts
+fn get_late_move_count(depth_left: u8) -> usize {
+    return 3 + depth_left as usize * depth_left as usize;
+}
+
+//0.75 + ln(depth) * ln(move number) / 2.25, rounded down
+fn get_lmr_table() -> [[u8; LMR_TABLE_SIZE]; LMR_TABLE_SIZE] {
+    let mut table = [[0; LMR_TABLE_SIZE]; LMR_TABLE_SIZE];
+
+    for depth in 1..LMR_TABLE_SIZE {
+        for move_number in 1..LMR_TABLE_SIZE {
+            table[depth][move_number] = (0.75 + (depth as f64).ln() * (move_number as f64).ln() / 2.25) as u8;
+        }
+    }
+
+    return table;
+}
+
 pub fn get_relative_endgame_eval(board: &BitBoard, table: &EndgameTable) -> (i32, GameState) {
     if board.get_all_piece_count() <= table.max_piece_count as u32 {
 
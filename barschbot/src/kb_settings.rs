@@ -5,15 +5,51 @@ pub struct KBSettings {
     pub max_depth: u8,
     pub end_game_table: bool,
     pub null_move_pruning: bool,
+    //Deeper null move searches get reduced more, and more so the further the eval is above beta
+    pub adaptive_null_move_reduction: bool,
+    //Null move pruning in pawn endings too, confirmed by a reduced normal search against zugzwang
+    pub null_move_verification: bool,
+    //Cut nodes near the leaves whose eval is far above beta
+    pub reverse_futility_pruning: bool,
+    //Drop into quiescence when the eval is far below alpha near the leaves
+    pub razoring: bool,
+    //Skip quiet moves near the leaves that cannot raise the eval to alpha
+    pub futility_pruning: bool,
+    //Skip the remaining quiet moves once enough have been searched near the leaves
+    pub late_move_pruning: bool,
+    //Null window searches for every move after the first, re-searched when they beat alpha
+    pub principal_variation_search: bool,
+    //Late quiet moves get searched shallower first
+    pub late_move_reductions: bool,
+    //Count a single repetition inside the search tree as a draw
+    pub twofold_repetition: bool,
     pub eval_factors: EvalFactorsInt,
-    pub min_search_time: u64
+    pub min_search_time: u64,
+    //Lazy SMP threads sharing the transposition table, 1 searches deterministically on the calling thread
+    pub thread_count: usize,
+    //Number of best root moves searched with their own line
+    pub multi_pv: usize,
 }
 
+pub const MAX_THREAD_COUNT: usize = 256;
+pub const MAX_MULTI_PV: usize = 256;
+
 pub const STANDARD_KB_SETTINGS: KBSettings = KBSettings { 
     max_depth: 6, 
     end_game_table: true, 
     null_move_pruning: true, 
+    adaptive_null_move_reduction: true,
+    null_move_verification: true,
+    reverse_futility_pruning: true,
+    razoring: true,
+    futility_pruning: true,
+    late_move_pruning: true,
+    principal_variation_search: true,
+    late_move_reductions: true,
+    twofold_repetition: true, 
     min_search_time: 0, 
+    thread_count: 1,
+    multi_pv: 1,
     eval_factors: STANDARD_EVAL_FACTORS };
 
 
@@ -0,0 +1,37 @@
+#![ allow(unused)]
+
+pub mod bit_board;
+pub mod bitboard_helper;
+pub mod slider_attacks;
+pub mod chess_move;
+pub mod square;
+pub mod piece_type;
+pub mod colored_piece_type;
+pub mod constants;
+pub mod zoberist_hash;
+pub mod fen;
+pub mod game;
+pub mod game_result;
+pub mod san;
+pub mod pgn;
+pub mod perft;
+
+pub mod evaluation;
+pub mod bb_settings;
+pub mod kb_settings;
+pub mod barsch_bot;
+pub mod karpfen_bot;
+pub mod move_picker;
+pub mod search_stats;
+pub mod search_info;
+pub mod transposition_table;
+pub mod time_manager;
+
+pub mod endgame_table;
+pub mod opening_book;
+
+pub mod uci;
+
+mod compact_hashmap;
+mod fill_arrays;
+mod piece_list;
@@ -1,12 +1,14 @@
 #![ allow(unused)]
 
-use bit_board::BitBoard;
-use chess_move::ChessMove;
+use barschbot::bit_board::BitBoard;
+use barschbot::chess_move::ChessMove;
 //use dataset::EvalBoards;
-use game::Game;
-use game::GameState;
+use barschbot::game::Game;
+use barschbot::game::GameState;
+use barschbot::{barsch_bot, bb_settings, endgame_table, kb_settings, uci};
 use rand::Rng;
 use rand::SeedableRng;
+#[cfg(feature = "gui")]
 use visualizer::Visualizer;
 
 //use game::Game;
@@ -15,113 +17,239 @@ use std::fs;
 use std::str;
 
 use crate::auto_tuning::compare_fish;
-use crate::bb_settings::BBSettings;
+use barschbot::bb_settings::BBSettings;
+use crate::cli::CliArgs;
 use crate::dataset::EvalBoards;
-use crate::endgame_table::EndgameTable;
-use crate::karpfen_bot::KarpfenBot;
-use crate::opening_book::OpeningBook;
+use barschbot::endgame_table::EndgameTable;
+use barschbot::karpfen_bot::KarpfenBot;
+use barschbot::kb_settings::KBSettings;
+use barschbot::opening_book::OpeningBook;
+use barschbot::perft::{self, PerftTable};
+use barschbot::slider_attacks::{self, SliderBackend};
 use rayon::prelude::*;
 use rand_chacha::ChaCha8Rng;
 
-mod zoberist_hash;
-
-mod bitboard_helper;
-//mod attack_board;
-mod constants;
-mod piece_list;
-mod chess_move;
-mod game;
-mod barsch_bot;
-mod bit_board;
-mod piece_type;
-mod colored_piece_type;
-mod square;
+//Experiment runners and the GUI, the engine itself lives in lib.rs
 mod dataset;
 mod perceptron_float;
+mod perceptron_int;
+#[cfg(feature = "gui")]
 mod visualizer;
-mod evaluation;
-mod endgame_table;
-mod bb_settings;
-mod opening_book;
 mod match_handler;
 mod auto_tuning;
-mod compact_hashmap;
-mod karpfen_bot;
-mod search_stats;
 mod benchmark;
-mod kb_settings;
-mod perceptron_int;
+mod cli;
 
-mod fill_arrays;
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let cli = CliArgs::parse(&args);
+
+    if let Some(name) = cli.get("slider") {
+        match SliderBackend::from_str(name) {
+            Some(SliderBackend::Pext) if !slider_attacks::pext_available() => cli::exit_with_error("This cpu does not support pext"),
+            Some(backend) => slider_attacks::set_backend(backend),
+            None => cli::exit_with_error(&format!("Unknown --slider backend: {}", name)),
+        }
+    }
 
-const FEN_PATH: &str = "C:\\Users\\hmart\\Documents\\GitHub\\BarschBot\\data\\Fens.txt";
+    match cli.command.as_str() {
+        "uci" => {
+            let (endgame_table, opening_book) = load_files(&cli);
 
-//use std::env;
-fn main() {
-    calc_rand_game_distr();
+            let mut bot = KarpfenBot::new();
+            bot.set_settings(get_kb_settings(&cli));
 
-    return;
+            uci::uci_loop(&mut bot, &opening_book, &endgame_table);
+        },
 
-    /* 
-    let dataset = EvalBoards::load("C:\\Users\\hmart\\Documents\\GitHub\\BarschBot\\data\\chessData.csv");
-    
-    let input_set = dataset.create_input_set_int();
-    let output_set: Vec<f64> = dataset.create_output_set().iter().map(|x| *x as f64).collect();
+        "perft" => run_perft(&cli),
 
-    let mut nn = perceptron_float::Perceptron::new(input_set[0].len());
-    let factors = kb_settings::STANDARD_EVAL_FACTORS.values;
+        "bench" => benchmark::benchmark(),
 
-    for i in 0..factors.len() {
-        nn.weights[i] = factors[i] as i32 as f64;
-    }
+        "puzzles" => {
+            let (endgame_table, opening_book) = load_files(&cli);
 
-    for i in 0..factors.len() {
-        nn.weights[i + factors.len()] = (factors[i] >> 32) as f64;
-    }
+            play_all_puzzles(cli.get_required("puzzles"), &opening_book, &endgame_table, &get_bb_settings(&cli), cli.get_thread_count());
+        },
 
+        "match" => {
+            let (endgame_table, opening_book) = load_files(&cli);
+            let fens = load_fens(cli.get_required("fens"));
 
-    nn.randomize_weights();
-    nn.gauss_newton(&input_set, &output_set);
-    //nn.gradient_descent(&input_set, &output_set);
-    */
+            let (w, l, d) = compare_fish(&fens, &opening_book, &endgame_table, &get_bb_settings(&cli), &get_kb_settings(&cli), cli.get_thread_count(), cli.get("pgn"));
     
+            auto_tuning::print_confidence(w, l, d);
+        },
+
+        "tune" => {
+            let (endgame_table, opening_book) = load_files(&cli);
+            let fens = load_fens(cli.get_required("fens"));
+
+            auto_tuning::auto_tune(&&fens, &opening_book, &endgame_table, get_bb_settings(&cli), cli.get("stockfish").unwrap_or("stockfish"), cli.get_thread_count());
+        },
+
+        "gen-egtb" => {
+            let pieces = cli.get_number("pieces").unwrap_or(4);
+
+            let table = EndgameTable::new(&endgame_table::gen_legal_boards(&endgame_table::generate_type_fields(pieces as usize)), pieces);
+            table.store_data(cli.get("table").unwrap_or("."));
+        },
+
+        #[cfg(feature = "gui")]
+        "play" => {
+            let (endgame_table, opening_book) = load_files(&cli);
+            let mut game = match cli.get("fen") {
+                Some(fen) => Game::try_from_fen(fen).unwrap_or_else(|e| cli::exit_with_error(&format!("Invalid --fen: {}", e))),
+                None => Game::get_start_position(),
+            };
+
+            let human_turn = game.is_whites_turn() != cli.has_flag("black");
+
+            match cli.get("bot").unwrap_or("karpfen") {
+                "barsch" => match_handler::player_vs_barsch(&mut game, human_turn, &get_bb_settings(&cli), &endgame_table, &opening_book),
+                "karpfen" => {
+                    let mut bot = KarpfenBot::new();
+                    bot.set_settings(get_kb_settings(&cli));
+
+                    match_handler::player_vs_karpfen(&mut game, human_turn, &mut bot, &opening_book, &endgame_table);
+                },
+                other => cli::exit_with_error(&format!("Unknown bot: {}", other)),
+            }
+        },
 
-    let (endgame_table, opening_book) = load_files();
-    let (w, l, d) = compare_fish(&load_fens(FEN_PATH), &opening_book, &endgame_table, &bb_settings::STANDARD_BB_SETTINGS, &kb_settings::STANDARD_KB_SETTINGS);
-    
-    auto_tuning::print_confidence(w, l, d);
+        #[cfg(not(feature = "gui"))]
+        "play" => cli::exit_with_error("play needs the visualizer, build with --features gui"),
 
+        "random-games" => calc_rand_game_distr(),
 
-    //match_handler::player_vs_karpfen(&mut Game::get_start_position(), true, &mut KarpfenBot::new(), &opening_book, &endgame_table);
+        "help" | "--help" | "-h" => println!("{}", cli::USAGE),
 
-    println!("Done");
+        other => cli::exit_with_error(&format!("Unknown command: {}", other)),
+    }
 }
 
-fn load_files() -> (EndgameTable, OpeningBook) {
-    let table = EndgameTable::load(4);
+fn load_files(cli: &CliArgs) -> (EndgameTable, OpeningBook) {
+    let table = match cli.get("table") {
+        Some(dir) => EndgameTable::load(dir, cli.get_number("pieces").unwrap_or(4)),
+        None => EndgameTable::empty(),
+    };
 
-    let book = OpeningBook::load_from_file("C:\\Users\\hmart\\Documents\\GitHub\\BarschBot\\data\\book.txt");
+    let book = match cli.get("book") {
+        Some(path) => OpeningBook::load_from_file(path),
+        None => OpeningBook::new(),
+    };
 
     return (table, book);
 }
 
+fn get_bb_settings(cli: &CliArgs) -> BBSettings {
+    let mut settings = bb_settings::STANDARD_BB_SETTINGS;
+
+    if let Some(depth) = cli.get_number("depth") {
+        settings.max_depth = depth;
+    }
+
+    return settings;
+}
+
+fn get_kb_settings(cli: &CliArgs) -> KBSettings {
+    let mut settings = kb_settings::STANDARD_KB_SETTINGS;
+
+    if let Some(depth) = cli.get_number("depth") {
+        settings.max_depth = depth;
+    }
+
+    //A/B tests of single search features, e.g. --disable lmr,pvs
+    for feature in cli.get("disable").unwrap_or("").split(',').filter(|f| f.len() > 0) {
+        match feature {
+            "pvs" => settings.principal_variation_search = false,
+            "lmr" => settings.late_move_reductions = false,
+            "nmp" => settings.null_move_pruning = false,
+            "adaptive-nmp" => settings.adaptive_null_move_reduction = false,
+            "nmp-verification" => settings.null_move_verification = false,
+            "rfp" => settings.reverse_futility_pruning = false,
+            "razoring" => settings.razoring = false,
+            "futility" => settings.futility_pruning = false,
+            "lmp" => settings.late_move_pruning = false,
+            _ => cli::exit_with_error(&format!("Unknown search feature for --disable: {}", feature)),
+        }
+    }
+
+    return settings;
+}
+
+fn run_perft(cli: &CliArgs) {
+    let thread_count = cli.get_thread_count();
+    let table = cli.get_number::<usize>("hash").map(|mb| PerftTable::new(mb));
+    let table = table.as_ref();
+    let use_game = cli.has_flag("game");
+
+    if let Some(fen) = cli.get("fen") {
+        let mut game = Game::try_from_fen(fen).unwrap_or_else(|e| cli::exit_with_error(&format!("Invalid --fen: {}", e)));
+        let board = game.get_board();
+        let max_depth = cli.get_number("depth").unwrap_or(5);
+
+        if cli.has_flag("divide") {
+            perft::print_divide(&board, max_depth, thread_count, table);
+            return;
+        }
+
+        for d in 1..=max_depth {
+            let start = Instant::now();
+            let nodes = if use_game { perft::perft_game(&mut game, d) } else { perft::perft_parallel(&board, d, thread_count, table) };
+
+            println!("Depth: {} -> {} ({:?})", d, nodes, start.elapsed());
+        }
+
+        return;
+    }
+
+    let positions = match cli.get("epd") {
+        Some(path) => perft::load_epd(path).unwrap_or_else(|e| cli::exit_with_error(&format!("Could not load {}: {}", path, e))),
+        None => perft::get_standard_positions(),
+    };
+
+    let failed = perft::run_suite(&positions, cli.get_number("depth").unwrap_or(5), |position, depth| {
+        if use_game {
+            return perft::perft_game(&mut Game::from_fen(&position.fen), depth);
+        }
+
+        return perft::perft_parallel(&position.get_board(), depth, thread_count, table);
+    });
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
 fn load_fens(path: &str) -> Vec<String> {
     
     let contents = fs::read_to_string(path).unwrap();
-    let ret: Vec<String> = contents
-        .lines()
-        .map(|line| line.split(",").next().unwrap().to_string())
-        .collect();
+    let mut ret = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let fen = line.split(",").next().unwrap().trim();
+
+        if fen.len() == 0 {
+            continue;
+        }
+
+        //Skip broken lines instead of panicking halfway through a long match
+        match Game::try_from_fen(fen) {
+            Ok(_) => ret.push(fen.to_string()),
+            Err(e) => println!("Skipping line {} of {}: {}", i + 1, path, e),
+        }
+    }
 
-    println!("Loaded {} fens from: {}", ret.len(), path.split("\\").last().unwrap());
+    println!("Loaded {} fens from: {}", ret.len(), path);
 
     return ret;
 }
 
-fn load_lichess_puzzles() -> Vec<(String, Vec<ChessMove>)>{
+fn load_lichess_puzzles(path: &str) -> Vec<(String, Vec<ChessMove>)>{
     //PuzzleId, FEN, Moves, Rating, RatingDeviation, Popularity, NbPlays, Themes, GameUrl, OpeningTags
 
-    let contents = fs::read_to_string("C:\\Users\\hmart\\Documents\\GitHub\\Chess-Challenge\\Rust\\data\\lichess_db_puzzle.csv").unwrap();
+    let contents = fs::read_to_string(path).unwrap();
     let ret: Vec<(String, Vec<ChessMove>)> = contents
         .lines()
         .skip(1)
@@ -147,18 +275,19 @@ fn load_lichess_puzzles() -> Vec<(String, Vec<ChessMove>)>{
 //Depth 2: 2579431 / 3678110 (70.12925%)
 
 
-fn play_all_puzzles(book: &OpeningBook, table: &EndgameTable ) {
-    let mut puzzles = load_lichess_puzzles();
+fn play_all_puzzles(path: &str, book: &OpeningBook, table: &EndgameTable, settings: &BBSettings, thread_count: usize) {
+    let mut puzzles = load_lichess_puzzles(path);
 
 
     println!("Loaded {} puzzles", puzzles.len());
 
     //let mut app = Visualizer::new();
     let mut rng = rand::thread_rng();
-    const THREAD_COUNT: usize = 13;
     let counter = puzzles.len();
 
-    puzzles.par_chunks_mut(counter / THREAD_COUNT).for_each(|slice| {
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(thread_count).build().unwrap();
+
+    pool.install(|| puzzles.par_chunks_mut((counter / thread_count).max(1)).for_each(|slice| {
         let mut correct = 0;
         let mut counter = 0;
         for (fen, moves) in slice {
@@ -170,7 +299,7 @@ fn play_all_puzzles(book: &OpeningBook, table: &EndgameTable ) {
                 
                 let cm = if i % 2 == 0 {
                     let fm = game.get_uci_move(moves[i].get_uci());
-                    //println!("Puzzle move: {}", fm.get_board_name(&game.get_board()));
+                    //println!("Puzzle move: {}", fm.get_san(&game.get_board()));
                     
                     fm
                 }
@@ -178,7 +307,7 @@ fn play_all_puzzles(book: &OpeningBook, table: &EndgameTable ) {
                     //let ml = game.get_legal_moves();
                     //let bmove = ml[rng.gen_range(0..ml.len())];
     
-                    let bmove = barsch_bot::get_best_move(&mut game, table, &bb_settings::STANDARD_BB_SETTINGS, book);
+                    let bmove = barsch_bot::get_best_move(&mut game, table, settings, book);
                     
                     //println!("Expected: {} Barsch: {}", moves[i].get_uci(), bmove.get_uci());
                     
@@ -210,7 +339,7 @@ fn play_all_puzzles(book: &OpeningBook, table: &EndgameTable ) {
         }
     
 
-    });
+    }));
 
 
     let mut cc = 0;
@@ -223,63 +352,24 @@ fn play_all_puzzles(book: &OpeningBook, table: &EndgameTable ) {
     println!("{} / {} ({}%)", cc, counter, cc as f32 * 100.0 / counter as f32);
 }
 
-fn check_all_perft_board() {
-    println!("Checking all fens");
-    const FENS: [&str; 6] = [
-        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ", 
-        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ",
-        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ",
-        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
-        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8  ",
-        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 "
-    ];
-
-    const MAX_DEPTHS: [u8; 6] = [8, 7, 9, 7, 6, 7];
-    const RESULTS: [&[u64]; 6] = [
-        &[1, 20, 400, 8902, 197281, 4865609, 119060324, 3195901860, 84998978956],
-        &[1, 48, 2039, 97862, 4085603, 193690690, 8031647685],
-        &[1, 14, 191, 2812, 43238, 674624, 11030083, 178633661, 3009794393],
-        &[1, 6, 264, 9467, 422333, 15833292, 706045033],
-        &[1, 44, 1486, 62379, 2103487, 89941194],
-        &[1, 46, 2079, 89890, 3894594, 164075551, 6923051137],
-    ];
-
-    for i in 0..6 {
-        println!("Index: {}", i + 1);
-        let bb = BitBoard::from_fen(FENS[i]);
-        //bb.print();
-        let target_res = RESULTS[i];
-        let mut sum = 0;
-
-        let mut start = Instant::now();
-        for d in 0..MAX_DEPTHS[i] {
-            
-            let mut res = PerftRes::new();
-            
-            let pair = dfs_board(bb, d, &mut res);
-
-            let count = res.positions;    
-            sum += count;
-
-            if count != target_res[d as usize] {
-                print!("Depth: {} -> {}", d, count);
-                println!(" should be: {}", target_res[d as usize]);
-            }            
-        }
-
-        let duration = start.elapsed();
-        println!("Time: {:?} Ratio: {} k boards per second", duration, (sum as u128) / duration.as_millis());
-    }
-}
-
 fn calc_rand_game_distr() {
     let mut rng = ChaCha8Rng::seed_from_u64(0);
     let mut results = [0; 6];
 
+    #[cfg(feature = "gui")]
     let mut vis = Visualizer::new();
+
     const COUNT: u64 = 100_000;
     for _ in 0..COUNT {
-        let res = play_random_game(&mut rng, &mut vis);
+        let mut game = play_random_game(&mut rng);
+        let res = game.get_game_state();
+
+        //Have a look at the unusually long mates
+        #[cfg(feature = "gui")]
+        if (res == GameState::WhiteCheckmate || res == GameState::BlackCheckmate) && game.move_depth() > 500 {
+            vis.render_board(&game.get_board().type_field, game.last_move(), false);
+            std::thread::sleep(std::time::Duration::from_millis(2000));
+        }
 
         let index = match res {
             GameState::WhiteCheckmate => 0,
@@ -305,7 +395,7 @@ fn calc_rand_game_distr() {
     println!("InsuffMat:  {:0.1}%", results[5] as f64 / COUNT as f64 * 100.0);
 }
 
-fn play_random_game(rng: &mut ChaCha8Rng, renderer: &mut Visualizer) -> GameState {
+fn play_random_game(rng: &mut ChaCha8Rng) -> Game {
     let mut game = Game::get_start_position();
 
     while game.get_game_state() == GameState::Undecided {
@@ -322,68 +412,9 @@ fn play_random_game(rng: &mut ChaCha8Rng, renderer: &mut Visualizer) -> GameStat
     }
 
 
-    if (game.get_game_state() == GameState::WhiteCheckmate || game.get_game_state() == GameState::BlackCheckmate)  && game.move_depth() > 500 {
-        renderer.render_board(&game.get_board().type_field, game.last_move(), false);
-        // println!("White wins");
-        std::thread::sleep(std::time::Duration::from_millis(2000));        
-    }
-    
-
     // println!("Game ended: {}", game.get_game_state().to_string());s
 
-
-
-    return game.get_game_state();
-}
-
-fn check_all_perft_game() {
-    println!("Checking all fens");
-    const FENS: [&str; 6] = [
-        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ", 
-        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ",
-        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ",
-        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
-        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8  ",
-        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 "
-    ];
-
-    const MAX_DEPTHS: [u8; 6] = [8, 7, 9, 7, 6, 7];
-    const RESULTS: [&[u64]; 6] = [
-        &[1, 20, 400, 8902, 197281, 4865609, 119060324, 3195901860, 84998978956],
-        &[1, 48, 2039, 97862, 4085603, 193690690, 8031647685],
-        &[1, 14, 191, 2812, 43238, 674624, 11030083, 178633661, 3009794393],
-        &[1, 6, 264, 9467, 422333, 15833292, 706045033],
-        &[1, 44, 1486, 62379, 2103487, 89941194],
-        &[1, 46, 2079, 89890, 3894594, 164075551, 6923051137],
-    ];
-
-    for i in 0..6 {
-        println!("Index: {}", i + 1);
-        //let bb = BitBoard::from_fen(FENS[i]);
-        let mut game = Game::from_fen(FENS[i]);
-        //bb.print();
-        let target_res = RESULTS[i];
-        let mut sum = 0;
-
-        let mut start = Instant::now();
-        for d in 0..MAX_DEPTHS[i] {
-            
-            let mut res = PerftRes::new();
-            
-            let pair = dfs_game(&mut game, d, &mut res);
-
-            let count = res.positions;    
-            sum += count;
-
-            if count != target_res[d as usize] {
-                print!("Depth: {} -> {}", d, count);
-                println!(" should be: {}", target_res[d as usize]);
-            }            
-        }
-
-        let duration = start.elapsed();
-        println!("Time: {:?} Ratio: {} k boards per second", duration, (sum as u128) / duration.as_millis());
-    }
+    return game;
 }
 
 fn benchmark_moves(b: BitBoard) {
@@ -391,14 +422,12 @@ fn benchmark_moves(b: BitBoard) {
 
     for i in 0..100 {
         let mut s = "".to_owned(); 
-        let mut res = PerftRes::new();
 
-        let pair = dfs_board(b, i, &mut res);
+        let count = perft::perft(&b, i);
         //let pair = dfs_fast(b, b.generate_pseudo_legal_moves(), i);
         //fs::write("rust.txt", &s).expect("Unable to write file");
 
-        print!("Depth: {} ", i);
-        res.print();
+        print!("Depth: {} Pos: {} ", i, count);
         let duration = start.elapsed();
         println!("{:?}", duration);
     }
@@ -469,62 +498,6 @@ fn print_tree(board: BitBoard, depth_left: u8, max_depth: u8) {
     }
 }
 
-struct PerftRes {
-    pub positions: u64,
-    pub captures: u64,
-    pub ep: u64,
-    pub castles: u64,
-    pub promotions: u64,
-    pub checks: u64,
-    pub double_checks: u64
-}
-impl PerftRes {
-    pub fn new() -> Self {
-        return PerftRes { positions: 0, captures: 0, ep: 0, castles: 0, promotions: 0, checks: 0, double_checks: 0 };
-    }
-
-    pub fn print(&self) {
-        println!("Pos: {} caps: {} ep: {} castles: {} prom: {} checks: {} double checks: {}", self.positions, self.captures, self.ep, self.castles, self.promotions, self.checks, self.double_checks);
-    }
-}
-
-fn dfs_board(board: BitBoard, depth_left: u8, res: &mut PerftRes) {
-    //list.sort_unstable_by(|a, b| { return a.get_uci().cmp(&b.get_uci())});
-    if depth_left == 0 {
-        res.positions += 1;
-        return;
-    }
-    
-    let mut list = board.get_legal_moves();
-
-    for m in list {
-        let mut kek = board.clone();
-        kek.make_move(m);
-
-        dfs_board(kek, depth_left - 1, res);
-    }
-    
-}
-
-
-fn dfs_game(game: &mut Game, depth_left: u8, res: &mut PerftRes) {
-    if depth_left == 0 {
-        res.positions += 1;
-        return;
-    }
-    
-    let list = game.get_legal_moves();
-
-    for m in list {
-        game.make_move(m);
-
-        dfs_game(game, depth_left - 1, res);
-
-        game.undo_move();
-    }
-}
-
-
 pub fn print_int(value: u64, max_digits: u8) {
     let length = value.to_string().len();
        
@@ -1,8 +1,11 @@
 use std::{process::{Child, Stdio, Command}, io::{BufWriter, BufReader, Write, BufRead, self}, time::{Duration, Instant}};
 
-use crate::{barsch_bot, bb_settings::BBSettings, chess_move::{self, ChessMove}, endgame_table::{self, EndgameTable}, game::{Game, GameState}, karpfen_bot::{self, KarpfenBot}, opening_book::{self, OpeningBook}, square::{self, Square}, visualizer::Visualizer};
+use barschbot::{barsch_bot, bb_settings::BBSettings, chess_move::{self, ChessMove}, endgame_table::{self, EndgameTable}, game::{Game, GameState}, game_result::{GameResult, ResultReason}, karpfen_bot::{self, KarpfenBot}, opening_book::{self, OpeningBook}, pgn, square::{self, Square}};
+#[cfg(feature = "gui")]
+use crate::visualizer::Visualizer;
 
 
+#[cfg(feature = "gui")]
 pub fn get_human_move(app: &mut Visualizer, game: &mut Game) -> ChessMove {
     let mut moves = game.get_legal_moves();
     loop {
@@ -22,8 +25,8 @@ pub fn get_human_move(app: &mut Visualizer, game: &mut Game) -> ChessMove {
     }
 }
 
-pub fn get_stock_fish_process() -> Child {
-    return Command::new("C:\\Users\\hmart\\Desktop\\stockfish\\stockfish-windows-x86-64-avx2.exe")
+pub fn get_stock_fish_process(path: &str) -> Child {
+    return Command::new(path)
     .stdout(Stdio::piped())
     .stdin(Stdio::piped())
     .spawn()
@@ -36,7 +39,7 @@ fn get_stock_fish_move(game: &mut Game, cmd: &mut Child) -> ChessMove {
     let stdin = cmd.stdin.as_mut().unwrap();
     let mut stdin_writer = BufWriter::new(stdin);
     {
-        stdin_writer.write_all(format!("position fen {}\n", game.get_board().get_fen()).as_bytes());
+        stdin_writer.write_all(format!("position fen {}\n", game.get_fen()).as_bytes());
         stdin_writer.flush();
         stdin_writer.write_all(format!("go depth {}\n", DEPTH).as_bytes());
         stdin_writer.flush();
@@ -85,8 +88,8 @@ fn get_stock_fish_move(game: &mut Game, cmd: &mut Child) -> ChessMove {
     panic!("Stockfish made an illegal move?");
 }
 
-fn create_process_and_get_sf_move(game: &mut Game) -> ChessMove {
-    let mut cmd = get_stock_fish_process();
+fn create_process_and_get_sf_move(game: &mut Game, stockfish_path: &str) -> ChessMove {
+    let mut cmd = get_stock_fish_process(stockfish_path);
 
     return get_stock_fish_move(game, &mut cmd);
 }
@@ -99,6 +102,7 @@ fn get_karpfenbot_move(game: &mut Game, bot: &mut KarpfenBot, opening_book: &Ope
     return bot.get_best_move(game, opening_book, endgame_table);
 }
 
+#[cfg(feature = "gui")]
 pub fn player_vs_barsch(game: &mut Game, mut human_turn: bool, settings: &BBSettings, table: &EndgameTable, book: &OpeningBook) { 
     let mut app = Visualizer::new();
     let flip = false;
@@ -125,11 +129,12 @@ pub fn player_vs_barsch(game: &mut Game, mut human_turn: bool, settings: &BBSett
         app.render_board(&game.get_board().type_field, cm, flip);
     }
     
-    println!("Result: {}", game.get_game_state().to_string());
+    println!("Result: {}", game.get_game_result().unwrap().to_string());
     println!("{}", game.to_string());
 }
 
 
+#[cfg(feature = "gui")]
 pub fn player_vs_karpfen(game: &mut Game, mut human_turn: bool, bot: &mut KarpfenBot, opening_book: &OpeningBook, endgame_table: &EndgameTable) { 
     let mut app = Visualizer::new();
     let flip = false;
@@ -156,15 +161,16 @@ pub fn player_vs_karpfen(game: &mut Game, mut human_turn: bool, bot: &mut Karpfe
         app.render_board(&game.get_board().type_field, cm, flip);
     }
     
-    println!("Result: {}", game.get_game_state().to_string());
+    println!("Result: {}", game.get_game_result().unwrap().to_string());
     println!("{}", game.to_string());
 }
 
-pub fn barsch_vs_karpfen(game: &mut Game, opening_book: &OpeningBook, endgame_table: &EndgameTable, bb_setting: &BBSettings, karpfen_bot: &mut KarpfenBot, mut barsch_turn: bool) -> (GameState, Duration, Duration) {
+//move_comments gets one entry per move, KarpfenBot moves carry its eval and depth
+pub fn barsch_vs_karpfen(game: &mut Game, opening_book: &OpeningBook, endgame_table: &EndgameTable, bb_setting: &BBSettings, karpfen_bot: &mut KarpfenBot, mut barsch_turn: bool, move_comments: &mut Vec<String>) -> (GameResult, Duration, Duration) {
     let mut duration_1 = Duration::ZERO;
     let mut duration_2 = Duration::ZERO;
 
-    while game.get_game_state() == GameState::Undecided {
+    while game.get_game_result().is_none() {
         
         let start = Instant::now();
         let cm = if barsch_turn {
@@ -176,13 +182,19 @@ pub fn barsch_vs_karpfen(game: &mut Game, opening_book: &OpeningBook, endgame_ta
         if cm == chess_move::NULL_MOVE || !game.get_legal_moves().contains(&cm) {
             cm.print();
             println!("Illegal move by {} \n{}", if barsch_turn { "Barsch" } else { "Karpfen" }, game.to_string());
+
+            return (GameResult::loss_for(game.is_whites_turn(), ResultReason::IllegalMove), duration_1, duration_2);
         }
         
         if barsch_turn {
             duration_1 += start.elapsed();
+            move_comments.push(String::new());
         }
         else {
             duration_2 += start.elapsed();
+
+            let depth = karpfen_bot.get_last_depth();
+            move_comments.push(if depth > 0 { pgn::get_eval_comment(karpfen_bot.get_last_score(), depth) } else { String::new() });
         }
 
         barsch_turn = !barsch_turn;
@@ -190,15 +202,15 @@ pub fn barsch_vs_karpfen(game: &mut Game, opening_book: &OpeningBook, endgame_ta
         game.make_move(cm);
     }
 
-    return (game.get_game_state(), duration_1, duration_2);
+    return (game.get_game_result().unwrap(), duration_1, duration_2);
 }
 
-pub fn play_bot_game(game: &mut Game, table: &EndgameTable, book: &OpeningBook, bb_settings_a: &BBSettings , bb_settings_b: &BBSettings) -> (GameState, Duration, Duration) {
+pub fn play_bot_game(game: &mut Game, table: &EndgameTable, book: &OpeningBook, bb_settings_a: &BBSettings , bb_settings_b: &BBSettings) -> (GameResult, Duration, Duration) {
     let mut first_player = true;
     let mut duration_1 = Duration::ZERO;
     let mut duration_2 = Duration::ZERO;
 
-    while game.get_game_state() == GameState::Undecided {
+    while game.get_game_result().is_none() {
         let set = if first_player { bb_settings_a } else { bb_settings_b };
         let start = Instant::now();
         let cm = barsch_bot::get_best_move(game, table, set, book);
@@ -215,14 +227,14 @@ pub fn play_bot_game(game: &mut Game, table: &EndgameTable, book: &OpeningBook,
         game.make_move(cm);
     }
 
-    return (game.get_game_state(), duration_1, duration_2);
+    return (game.get_game_result().unwrap(), duration_1, duration_2);
 }
 
-pub fn barsch_vs_sf(game: &mut Game, bb_setting: &BBSettings, book: &OpeningBook, table: &EndgameTable, mut barsch_turn: bool, cmd: &mut Child) -> (GameState, Duration, Duration) {
+pub fn barsch_vs_sf(game: &mut Game, bb_setting: &BBSettings, book: &OpeningBook, table: &EndgameTable, mut barsch_turn: bool, cmd: &mut Child) -> (GameResult, Duration, Duration) {
     let mut duration_1 = Duration::ZERO;
     let mut duration_2 = Duration::ZERO;
 
-    while game.get_game_state() == GameState::Undecided {
+    while game.get_game_result().is_none() {
         
         let start = Instant::now();
         let cm = if barsch_turn {
@@ -230,6 +242,12 @@ pub fn barsch_vs_sf(game: &mut Game, bb_setting: &BBSettings, book: &OpeningBook
         } else {
             get_stock_fish_move(game, cmd)
         };
+
+        if cm == chess_move::NULL_MOVE || !game.get_legal_moves().contains(&cm) {
+            println!("Illegal move by {} \n{}", if barsch_turn { "Barsch" } else { "Stockfish" }, game.to_string());
+
+            return (GameResult::loss_for(game.is_whites_turn(), ResultReason::IllegalMove), duration_1, duration_2);
+        }
         
         if barsch_turn {
             duration_1 += start.elapsed();
@@ -243,13 +261,5 @@ pub fn barsch_vs_sf(game: &mut Game, bb_setting: &BBSettings, book: &OpeningBook
         game.make_move(cm);
     }
 
-    return (game.get_game_state(), duration_1, duration_2);
+    return (game.get_game_result().unwrap(), duration_1, duration_2);
 }
-
-fn uci_loop() {
-    let mut buffer = String::new();
-    let stdin = io::stdin(); // We get `Stdin` here.
-    stdin.read_line(&mut buffer);
-
-    println!("Reading: [{}]", buffer);
-}
\ No newline at end of file
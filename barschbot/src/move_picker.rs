@@ -0,0 +1,205 @@
+use arrayvec::ArrayVec;
+
+use crate::{bit_board::BitBoard, chess_move::{self, ChessMove}, colored_piece_type::ColoredPieceType, piece_type::PieceType};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PickerStage {
+    TTMove, GoodCaptures, Killer, CounterMove, Quiets, BadCaptures, Done
+}
+
+pub const STAGE_COUNT: usize = 6;
+pub const ALL_STAGES: [PickerStage; STAGE_COUNT] = [
+    PickerStage::TTMove, PickerStage::GoodCaptures, PickerStage::Killer, PickerStage::CounterMove, PickerStage::Quiets, PickerStage::BadCaptures
+];
+
+impl PickerStage {
+    pub fn to_string(&self) -> &str {
+        return match *self {
+            PickerStage::TTMove => "TT move",
+            PickerStage::GoodCaptures => "Good captures",
+            PickerStage::Killer => "Killer",
+            PickerStage::CounterMove => "Counter move",
+            PickerStage::Quiets => "Quiets",
+            PickerStage::BadCaptures => "Bad captures",
+            PickerStage::Done => "Done",
+        }
+    }
+
+    fn next(&self) -> PickerStage {
+        return match *self {
+            PickerStage::TTMove => PickerStage::GoodCaptures,
+            PickerStage::GoodCaptures => PickerStage::Killer,
+            PickerStage::Killer => PickerStage::CounterMove,
+            PickerStage::CounterMove => PickerStage::Quiets,
+            PickerStage::Quiets => PickerStage::BadCaptures,
+            PickerStage::BadCaptures => PickerStage::Done,
+            PickerStage::Done => PickerStage::Done,
+        }
+    }
+}
+
+const PIECE_VALUES: [u64; 6] = [1, 3, 3, 5, 11, 100];
+
+//Hands out pseudo legal moves stage by stage. Each move class is only generated once the stage before
+//is used up, and the best remaining move is picked by a selection step instead of sorting the whole list
+pub struct MovePicker {
+    board: BitBoard,
+    stage: PickerStage,
+    //Quiescence skips killer, counter move and quiets unless in check
+    skip_quiets: bool,
+
+    tt_move: ChessMove,
+    killer: ChessMove,
+    counter_move: ChessMove,
+
+    //Moves of the current stage with their order key, generated when the stage is first reached
+    generated: bool,
+    moves: ArrayVec<(ChessMove, u64), 200>,
+    bad_captures: ArrayVec<(ChessMove, u64), 200>,
+}
+
+impl MovePicker {
+    pub fn new(board: &BitBoard, tt_move: ChessMove, killer: ChessMove, counter_move: ChessMove) -> MovePicker {
+        //Refutation moves come from other positions, they have to fit this board
+        let valid = |m: ChessMove| board.is_pseudo_legal(m);
+
+        let tt_move = if valid(tt_move) { tt_move } else { chess_move::NULL_MOVE };
+        let killer = if killer != tt_move && is_quiet(killer) && valid(killer) { killer } else { chess_move::NULL_MOVE };
+        let counter_move = if counter_move != tt_move && counter_move != killer && is_quiet(counter_move) && valid(counter_move) { counter_move } else { chess_move::NULL_MOVE };
+
+        return MovePicker { board: *board, stage: PickerStage::TTMove, skip_quiets: false, tt_move, killer, counter_move,
+            generated: false, moves: ArrayVec::new(), bad_captures: ArrayVec::new() };
+    }
+
+    pub fn new_quiescence(board: &BitBoard, tt_move: ChessMove, in_check: bool) -> MovePicker {
+        let mut picker = MovePicker::new(board, tt_move, chess_move::NULL_MOVE, chess_move::NULL_MOVE);
+
+        if !in_check {
+            picker.skip_quiets = true;
+
+            if is_quiet(picker.tt_move) {
+                picker.tt_move = chess_move::NULL_MOVE;
+            }
+        }
+
+        return picker;
+    }
+
+    //Next pseudo legal move and the stage it came from, the caller still has to check is_legal
+    pub fn next(&mut self, quiet_move_history: &[[u64; 64]; 64]) -> Option<(ChessMove, PickerStage)> {
+        loop {
+            match self.stage {
+                PickerStage::TTMove => {
+                    self.stage = self.stage.next();
+
+                    if !self.tt_move.is_null_move() {
+                        return Some((self.tt_move, PickerStage::TTMove));
+                    }
+                },
+
+                PickerStage::GoodCaptures => {
+                    if !self.generated {
+                        self.generated = true;
+
+                        for m in self.board.generate_pseudo_legal_captures() {
+                            if m == self.tt_move {
+                                continue;
+                            }
+
+                            if !self.board.see_ge(m, 0) {
+                                self.bad_captures.push((m, get_capture_key(m)));
+                            }
+                            else {
+                                self.moves.push((m, get_capture_key(m)));
+                            }
+                        }
+                    }
+
+                    if let Some(m) = pick_best(&mut self.moves) {
+                        return Some((m, PickerStage::GoodCaptures));
+                    }
+
+                    self.stage = if self.skip_quiets { PickerStage::BadCaptures } else { self.stage.next() };
+                    self.generated = false;
+                },
+
+                PickerStage::Killer => {
+                    self.stage = self.stage.next();
+
+                    if !self.killer.is_null_move() {
+                        return Some((self.killer, PickerStage::Killer));
+                    }
+                },
+
+                PickerStage::CounterMove => {
+                    self.stage = self.stage.next();
+
+                    if !self.counter_move.is_null_move() {
+                        return Some((self.counter_move, PickerStage::CounterMove));
+                    }
+                },
+
+                PickerStage::Quiets => {
+                    if !self.generated {
+                        self.generated = true;
+
+                        for m in self.board.generate_pseudo_legal_quiets() {
+                            if m != self.tt_move && m != self.killer && m != self.counter_move {
+                                self.moves.push((m, quiet_move_history[m.start_square as usize][m.target_square as usize]));
+                            }
+                        }
+                    }
+
+                    if let Some(m) = pick_best(&mut self.moves) {
+                        return Some((m, PickerStage::Quiets));
+                    }
+
+                    self.stage = self.stage.next();
+                },
+
+                PickerStage::BadCaptures => {
+                    if let Some(m) = pick_best(&mut self.bad_captures) {
+                        return Some((m, PickerStage::BadCaptures));
+                    }
+
+                    self.stage = self.stage.next();
+                },
+
+                PickerStage::Done => return None,
+            }
+        }
+    }
+}
+
+fn is_quiet(m: ChessMove) -> bool {
+    return !m.is_capture() && !m.is_promotion();
+}
+
+//Removes and returns the move with the highest key
+fn pick_best(moves: &mut ArrayVec<(ChessMove, u64), 200>) -> Option<ChessMove> {
+    if moves.len() == 0 {
+        return None;
+    }
+
+    let mut best = 0;
+    for i in 1..moves.len() {
+        if moves[i].1 > moves[best].1 {
+            best = i;
+        }
+    }
+
+    return Some(moves.swap_remove(best).0);
+}
+
+fn piece_value(cpt: ColoredPieceType) -> u64 {
+    return PIECE_VALUES[PieceType::from_cpt(cpt) as usize];
+}
+
+//MVV-LVA, promotions and en passant after all direct captures
+fn get_capture_key(m: ChessMove) -> u64 {
+    if m.is_direct_capture() {
+        return (1_u64 << 50) * piece_value(m.capture_piece_type) - piece_value(m.move_piece_type);
+    }
+
+    return 1_u64 << 45;
+}
@@ -21,17 +21,25 @@ impl OpeningBook {
             //println!("Line: {}", line);
             let parts = line.split(",").collect::<Vec<_>>();
 
+            if parts.len() < 2 {
+                continue;
+            }
+
             let board = BitBoard::from_fen(parts[0]);
             let hash = board.get_zoberist_hash();
-            
-            let list = board.get_legal_moves();
 
-            let m = list.iter().filter(|m| m.get_board_name(&board) == parts[1]).collect::<Vec<_>>()[0];
+            let m = match ChessMove::from_san(parts[1], &board) {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("Skipping book line {}: {}", line, e);
+                    continue;
+                }
+            };
 
             //board.print();
-            //println!("Book move {}", m.get_board_name(&board));
+            //println!("Book move {}", m.get_san(&board));
 
-            moves.insert(hash, *m);
+            moves.insert(hash, m);
         }
 
         return OpeningBook {
@@ -1,7 +1,6 @@
 use std::{ops::Mul, time::Instant};
 
 use na::{ComplexField, DMatrix};
-use opengl_graphics::error;
 use rand::prelude::*;
 use rand_distr::StandardNormal;
 use nalgebra as na;
@@ -1,4 +1,3 @@
-use std::os::windows;
 use rand::prelude::*;
 use rand_distr::StandardNormal;
 
@@ -0,0 +1,308 @@
+use std::{fmt, fs, sync::atomic::{AtomicU64, Ordering}, time::Instant};
+
+use rayon::prelude::*;
+
+use crate::{bit_board::BitBoard, chess_move::ChessMove, fen::FenError, game::Game};
+
+// https://www.chessprogramming.org/Perft_Results
+//Start positions with their node counts from depth 0 on
+pub const STANDARD_POSITIONS: [(&str, &[u64]); 6] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[1, 20, 400, 8902, 197281, 4865609, 119060324, 3195901860, 84998978956]),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[1, 48, 2039, 97862, 4085603, 193690690, 8031647685]),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[1, 14, 191, 2812, 43238, 674624, 11030083, 178633661, 3009794393]),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[1, 6, 264, 9467, 422333, 15833292, 706045033]),
+    ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[1, 44, 1486, 62379, 2103487, 89941194]),
+    ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[1, 46, 2079, 89890, 3894594, 164075551, 6923051137]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    InvalidFen(FenError),
+    InvalidDepth(String),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            EpdError::InvalidFen(e) => write!(f, "invalid fen: {}", e),
+            EpdError::InvalidDepth(s) => write!(f, "invalid depth entry \"{}\", expected D<depth> <nodes>", s),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+pub struct PerftPosition {
+    pub fen: String,
+    //(depth, expected nodes), sorted by depth
+    pub expected: Vec<(u8, u64)>,
+}
+
+impl PerftPosition {
+    pub fn get_board(&self) -> BitBoard {
+        return BitBoard::from_fen(&self.fen);
+    }
+}
+
+//Shared between threads without locking: the first word is the key xor the node count,
+//so torn writes from two threads fail the key check instead of returning wrong counts
+pub struct PerftTable {
+    entries: Vec<[AtomicU64; 2]>,
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> Self {
+        let count = (size_mb * 1024 * 1024 / std::mem::size_of::<[AtomicU64; 2]>()).max(1);
+        let entries = (0..count).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect();
+
+        return PerftTable { entries };
+    }
+
+    fn get_key(board: &BitBoard, depth: u8) -> u64 {
+        return board.get_zoberist_hash() ^ (depth as u64).wrapping_mul(0x9E3779B97F4A7C15);
+    }
+
+    fn probe(&self, key: u64) -> Option<u64> {
+        let entry = &self.entries[(key % self.entries.len() as u64) as usize];
+        let check = entry[0].load(Ordering::Relaxed);
+        let nodes = entry[1].load(Ordering::Relaxed);
+
+        if check ^ nodes == key {
+            return Some(nodes);
+        }
+
+        return None;
+    }
+
+    fn store(&self, key: u64, nodes: u64) {
+        let entry = &self.entries[(key % self.entries.len() as u64) as usize];
+        entry[0].store(key ^ nodes, Ordering::Relaxed);
+        entry[1].store(nodes, Ordering::Relaxed);
+    }
+}
+
+//Leaf nodes at depth, the last ply is bulk counted from the move list
+pub fn perft(board: &BitBoard, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let list = board.get_legal_moves();
+
+    if depth == 1 {
+        return list.len() as u64;
+    }
+
+    let mut sum = 0;
+    for m in list {
+        let mut child = board.clone();
+        child.make_move(m);
+
+        sum += perft(&child, depth - 1);
+    }
+
+    return sum;
+}
+
+//Same count from the pseudo legal generator, every move goes through is_legal
+pub fn perft_pseudo_legal(board: &BitBoard, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut sum = 0;
+    for m in board.generate_pseudo_legal_moves() {
+        if !board.is_legal(m) {
+            continue;
+        }
+
+        if depth == 1 {
+            sum += 1;
+            continue;
+        }
+
+        let mut child = board.clone();
+        child.make_move(m);
+
+        sum += perft_pseudo_legal(&child, depth - 1);
+    }
+
+    return sum;
+}
+
+pub fn perft_hashed(board: &BitBoard, depth: u8, table: &PerftTable) -> u64 {
+    if depth <= 1 {
+        return perft(board, depth);
+    }
+
+    let key = PerftTable::get_key(board, depth);
+    if let Some(nodes) = table.probe(key) {
+        return nodes;
+    }
+
+    let mut sum = 0;
+    for m in board.get_legal_moves() {
+        let mut child = board.clone();
+        child.make_move(m);
+
+        sum += perft_hashed(&child, depth - 1, table);
+    }
+
+    table.store(key, sum);
+
+    return sum;
+}
+
+//Goes through Game::make_move and undo_move, slower but checks the game stacks as well
+pub fn perft_game(game: &mut Game, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let list = game.get_legal_moves();
+
+    if depth == 1 {
+        return list.len() as u64;
+    }
+
+    let mut sum = 0;
+    for m in list {
+        game.make_move(m);
+        sum += perft_game(game, depth - 1);
+        game.undo_move();
+    }
+
+    return sum;
+}
+
+//Node count below every root move, the root moves are split over thread_count threads
+pub fn divide(board: &BitBoard, depth: u8, thread_count: usize, table: Option<&PerftTable>) -> Vec<(ChessMove, u64)> {
+    assert!(depth > 0);
+
+    let list = board.get_legal_moves();
+
+    let count_move = |m: &ChessMove| {
+        let mut child = board.clone();
+        child.make_move(*m);
+
+        let nodes = match table {
+            Some(table) => perft_hashed(&child, depth - 1, table),
+            None => perft(&child, depth - 1),
+        };
+
+        return (*m, nodes);
+    };
+
+    if thread_count <= 1 {
+        return list.iter().map(count_move).collect();
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(thread_count).build().unwrap();
+
+    return pool.install(|| list.par_iter().map(count_move).collect());
+}
+
+pub fn perft_parallel(board: &BitBoard, depth: u8, thread_count: usize, table: Option<&PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    return divide(board, depth, thread_count, table).iter().map(|(_, nodes)| nodes).sum();
+}
+
+//Stockfish style output, one "move: nodes" line per root move
+pub fn print_divide(board: &BitBoard, depth: u8, thread_count: usize, table: Option<&PerftTable>) {
+    let start = Instant::now();
+    let mut list = divide(board, depth, thread_count, table);
+    list.sort_by_key(|(m, _)| m.get_uci());
+
+    for (m, nodes) in &list {
+        println!("{}: {}", m.get_uci(), nodes);
+    }
+
+    println!();
+    println!("Nodes searched: {} ({:?})", list.iter().map(|(_, nodes)| nodes).sum::<u64>(), start.elapsed());
+}
+
+//perftsuite format: "<fen> ;D1 20 ;D2 400 ;D3 8902"
+pub fn parse_epd_line(line: &str) -> Result<PerftPosition, EpdError> {
+    let mut parts = line.split(';');
+
+    let fen = parts.next().unwrap_or("").trim().to_owned();
+    BitBoard::try_from_fen(&fen).map_err(|e| EpdError::InvalidFen(e))?;
+
+    let mut expected = Vec::new();
+    for part in parts {
+        let part = part.trim();
+        if part.len() == 0 {
+            continue;
+        }
+
+        let invalid = || EpdError::InvalidDepth(part.to_owned());
+
+        let (depth, nodes) = part.split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let depth = depth.strip_prefix('D').and_then(|d| d.parse::<u8>().ok()).ok_or_else(invalid)?;
+        let nodes = nodes.trim().parse::<u64>().map_err(|_| invalid())?;
+
+        expected.push((depth, nodes));
+    }
+
+    expected.sort();
+
+    return Ok(PerftPosition { fen, expected });
+}
+
+//Skips empty lines and # comments, stops at the first broken line
+pub fn load_epd(path: &str) -> Result<Vec<PerftPosition>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    let mut positions = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.len() == 0 || line.starts_with('#') {
+            continue;
+        }
+
+        positions.push(parse_epd_line(line).map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+
+    return Ok(positions);
+}
+
+pub fn get_standard_positions() -> Vec<PerftPosition> {
+    return STANDARD_POSITIONS.iter().map(|(fen, counts)| PerftPosition {
+        fen: fen.to_string(),
+        expected: counts.iter().enumerate().skip(1).map(|(d, n)| (d as u8, *n)).collect(),
+    }).collect();
+}
+
+//Checks every expected count up to max_depth with count_nodes(position, depth), returns the number of mismatches
+pub fn run_suite(positions: &[PerftPosition], max_depth: u8, count_nodes: impl Fn(&PerftPosition, u8) -> u64) -> u32 {
+    let mut failed = 0;
+    let start = Instant::now();
+    let mut total_nodes = 0;
+
+    for (i, position) in positions.iter().enumerate() {
+        println!("Position {}: {}", i + 1, position.fen);
+
+        for (depth, expected) in position.expected.iter().filter(|(d, _)| *d <= max_depth) {
+            let depth_start = Instant::now();
+            let nodes = count_nodes(position, *depth);
+            total_nodes += nodes;
+
+            if nodes == *expected {
+                println!("\tDepth: {} -> {} ({:?})", depth, nodes, depth_start.elapsed());
+            }
+            else {
+                println!("\tDepth: {} -> {} should be: {}", depth, nodes, expected);
+                failed += 1;
+            }
+        }
+    }
+
+    let duration = start.elapsed();
+    println!("{} positions, {} failed, {} nodes in {:?} ({} k nodes per second)", positions.len(), failed, total_nodes, duration,
+        total_nodes as u128 / duration.as_millis().max(1));
+
+    return failed;
+}
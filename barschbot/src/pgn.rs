@@ -0,0 +1,361 @@
+use std::{fmt, fs::{self, OpenOptions}, io::{self, Write}};
+
+use crate::{chess_move::{self, ChessMove}, fen::FenError, game::Game, game_result::GameResult, san::SanError, search_info::InfoScore};
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const MAX_LINE_LENGTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    InvalidTag(String),
+    InvalidFen(FenError),
+    //game index in the file, move text, reason
+    IllegalMove(usize, String, SanError),
+    //game index in the file, a "--" in check or without legal moves
+    IllegalNullMove(usize),
+    UnterminatedComment,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            PgnError::InvalidTag(s) => write!(f, "invalid tag pair: {}", s),
+            PgnError::InvalidFen(e) => write!(f, "invalid FEN tag: {}", e),
+            PgnError::IllegalMove(game, san, e) => write!(f, "game {}: illegal move {}: {}", game + 1, san, e),
+            PgnError::IllegalNullMove(game) => write!(f, "game {}: null move in check or without legal moves", game + 1),
+            PgnError::UnterminatedComment => write!(f, "unterminated comment"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    pub game: Game,
+    //Comment in front of the first move
+    pub start_comment: String,
+    //One entry per played move, empty if the move has no comment
+    pub comments: Vec<String>,
+}
+
+impl PgnGame {
+    pub fn new(game: Game) -> PgnGame {
+        let mut tags = Vec::new();
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Date" => "????.??.??",
+                "Result" => "*",
+                _ => "?",
+            };
+
+            tags.push((name.to_owned(), value.to_owned()));
+        }
+
+        let comments = vec![String::new(); game.get_moves().len()];
+
+        return PgnGame { tags, game, start_comment: String::new(), comments };
+    }
+
+    //Takes the result from the final position of the game, "*" if it is still running
+    pub fn from_game(mut game: Game) -> PgnGame {
+        let result = game.get_game_result();
+
+        let mut pgn = PgnGame::new(game);
+        if let Some(result) = result {
+            pgn.set_result(&result);
+        }
+
+        return pgn;
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        return self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+    }
+
+    pub fn get_result(&self) -> &str {
+        return self.get_tag("Result").unwrap_or("*");
+    }
+
+    pub fn set_result(&mut self, result: &GameResult) {
+        self.set_tag("Result", result.get_pgn_result());
+        self.set_tag("Termination", result.get_termination());
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut s = "".to_owned();
+
+        let start_fen = self.game.get_start_fen();
+        let custom_start = start_fen != START_FEN;
+
+        for (name, value) in &self.tags {
+            if custom_start && (name == "SetUp" || name == "FEN") {
+                continue;
+            }
+
+            s += &format!("[{} \"{}\"]\n", name, escape(value));
+        }
+
+        if custom_start {
+            s += "[SetUp \"1\"]\n";
+            s += &format!("[FEN \"{}\"]\n", start_fen);
+        }
+
+        s += "\n";
+
+        let start = Game::from_fen(&start_fen);
+        let mut board = start.get_board();
+        let mut ply = (start.get_fullmove_number() - 1) * 2 + if board.is_whites_turn() { 0 } else { 1 };
+
+        let mut tokens = Vec::new();
+
+        if self.start_comment.len() > 0 {
+            tokens.push(format!("{{{}}}", self.start_comment));
+        }
+
+        let mut needs_number = true;
+        for (i, m) in self.game.get_moves().iter().enumerate() {
+            if board.is_whites_turn() {
+                tokens.push(format!("{}.", ply / 2 + 1));
+            }
+            else if needs_number {
+                tokens.push(format!("{}...", ply / 2 + 1));
+            }
+
+            tokens.push(m.get_san(&board));
+
+            needs_number = false;
+            if let Some(comment) = self.comments.get(i) {
+                if comment.len() > 0 {
+                    tokens.push(format!("{{{}}}", comment));
+                    needs_number = true;
+                }
+            }
+
+            board.make_move(*m);
+            ply += 1;
+        }
+
+        tokens.push(self.get_result().to_owned());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                s += "\n";
+                line_length = 0;
+            }
+
+            if line_length > 0 {
+                s += " ";
+                line_length += 1;
+            }
+
+            s += &token;
+            line_length += token.len();
+        }
+
+        s += "\n";
+
+        return s;
+    }
+}
+
+//cutechess style comment: score in pawns from the engines point of view and the search depth
+pub fn get_eval_comment(score: i32, depth: u8) -> String {
+    return match InfoScore::from_score(score) {
+        InfoScore::Mate(moves) => format!("{}M{}/{}", if moves > 0 { "+" } else { "-" }, moves.abs(), depth),
+        InfoScore::Centipawns(cp) => format!("{}{:.2}/{}", if cp >= 0 { "+" } else { "-" }, cp.abs() as f64 / 100.0, depth),
+    };
+}
+
+pub fn write_pgn(games: &[PgnGame]) -> String {
+    return games.iter().map(|g| g.to_pgn()).collect::<Vec<_>>().join("\n");
+}
+
+pub fn append_to_file(path: &str, game: &PgnGame) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    return file.write_all((game.to_pgn() + "\n").as_bytes());
+}
+
+pub fn load_file(path: &str) -> Result<Vec<PgnGame>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    return Ok(parse_pgn(&contents)?);
+}
+
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = Vec::new();
+
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let mut movetext = "".to_owned();
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        //Escape mechanism from the pgn spec, the whole line is ignored
+        if line.starts_with('%') {
+            continue;
+        }
+
+        if line.starts_with('[') && !in_comment(&movetext) {
+            //A tag after move text starts the next game
+            if movetext.trim().len() > 0 {
+                games.push(parse_game(games.len(), &tags, &movetext)?);
+                tags.clear();
+                movetext.clear();
+            }
+
+            tags.push(parse_tag(line)?);
+            continue;
+        }
+
+        movetext += line;
+        movetext += "\n";
+    }
+
+    if tags.len() > 0 || movetext.trim().len() > 0 {
+        games.push(parse_game(games.len(), &tags, &movetext)?);
+    }
+
+    return Ok(games);
+
+    fn in_comment(movetext: &str) -> bool {
+        return movetext.matches('{').count() > movetext.matches('}').count();
+    }
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), PgnError> {
+    let invalid = || PgnError::InvalidTag(line.to_owned());
+
+    let inner = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).ok_or_else(invalid)?.trim();
+    let (name, rest) = inner.split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let rest = rest.trim();
+
+    if !rest.starts_with('"') || !rest.ends_with('"') || rest.len() < 2 {
+        return Err(invalid());
+    }
+
+    let mut value = "".to_owned();
+    let mut escaped = false;
+    for c in rest[1..rest.len() - 1].chars() {
+        if escaped || c != '\\' {
+            value.push(c);
+            escaped = false;
+        }
+        else {
+            escaped = true;
+        }
+    }
+
+    return Ok((name.to_owned(), value));
+}
+
+fn parse_game(index: usize, tags: &Vec<(String, String)>, movetext: &str) -> Result<PgnGame, PgnError> {
+    let fen = tags.iter().find(|(n, _)| n == "FEN").map(|(_, v)| v.as_str()).unwrap_or(START_FEN);
+    let game = Game::try_from_fen(fen).map_err(|e| PgnError::InvalidFen(e))?;
+
+    let mut pgn = PgnGame::new(game);
+    for (name, value) in tags {
+        pgn.set_tag(name, value);
+    }
+
+    let chars = movetext.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    let mut variation_depth = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c == '{' {
+            let end = chars[i..].iter().position(|c| *c == '}').ok_or(PgnError::UnterminatedComment)? + i;
+
+            //Comments inside variations belong to moves we skip
+            if variation_depth == 0 {
+                let comment = chars[i + 1..end].iter().collect::<String>().trim().to_owned();
+                let last = pgn.comments.last_mut().unwrap_or(&mut pgn.start_comment);
+
+                if last.len() > 0 {
+                    last.push(' ');
+                }
+                *last += &comment;
+            }
+
+            i = end + 1;
+            continue;
+        }
+
+        if c == ';' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        if c == '(' || c == ')' {
+            variation_depth = if c == '(' { variation_depth + 1 } else { i32::max(variation_depth - 1, 0) };
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && !"{}();".contains(chars[i]) {
+            i += 1;
+        }
+
+        let token = chars[start..i].iter().collect::<String>();
+
+        if variation_depth > 0 || token.starts_with('$') {
+            continue;
+        }
+
+        if token == "1-0" || token == "0-1" || token == "1/2-1/2" || token == "*" {
+            pgn.set_tag("Result", &token);
+            continue;
+        }
+
+        //Move numbers, possibly glued to the move like "1.e4"
+        let san = match token.rfind('.') {
+            Some(p) => &token[p + 1..],
+            None if token.chars().all(|c| c.is_ascii_digit()) => "",
+            None => &token,
+        };
+
+        if san.len() == 0 {
+            continue;
+        }
+
+        let m = if san == "--" {
+            if pgn.game.get_board().in_check() || pgn.game.get_legal_moves().len() == 0 {
+                return Err(PgnError::IllegalNullMove(index));
+            }
+
+            chess_move::NULL_MOVE
+        }
+        else {
+            ChessMove::from_san(san, &pgn.game.get_board()).map_err(|e| PgnError::IllegalMove(index, san.to_owned(), e))?
+        };
+
+        pgn.game.make_move(m);
+        pgn.comments.push(String::new());
+    }
+
+    return Ok(pgn);
+}
+
+fn escape(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace('"', "\\\"");
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crate::{bit_board::BitBoard, chess_move::ChessMove, piece_type::PieceType, square::Square};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    InvalidSyntax(String),
+    NoMatchingMove(String),
+    Ambiguous(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            SanError::InvalidSyntax(s) => write!(f, "invalid san \"{}\"", s),
+            SanError::NoMatchingMove(s) => write!(f, "no legal move matches \"{}\"", s),
+            SanError::Ambiguous(s) => write!(f, "\"{}\" matches more than one legal move", s),
+        }
+    }
+}
+
+impl std::error::Error for SanError {}
+
+pub fn get_san(m: ChessMove, board: &BitBoard) -> String {
+    if m.is_null_move() {
+        return "--".to_owned();
+    }
+
+    let mut s = "".to_owned();
+
+    if m.is_castle() {
+        s += if (m.target_square as u8) < (m.start_square as u8) { "O-O-O" } else { "O-O" };
+    }
+    else {
+        let piece_type = PieceType::from_cpt(m.move_piece_type);
+
+        if piece_type == PieceType::Pawn {
+            if m.is_capture() {
+                s.push(m.start_square.file_char());
+            }
+        }
+        else {
+            s.push(piece_type.get_char());
+            s += &get_disambiguation(m, board);
+        }
+
+        if m.is_capture() {
+            s += "x";
+        }
+
+        s += &m.target_square.to_string();
+
+        if m.is_promotion() {
+            s += "=";
+            s.push(PieceType::from_cpt(m.promotion_piece_type).get_char());
+        }
+    }
+
+    let mut after = board.clone();
+    after.make_move(m);
+
+    if after.in_check() {
+        s += if after.get_legal_moves().len() == 0 { "#" } else { "+" };
+    }
+
+    return s;
+
+    //Shortest prefix that tells the move apart: file, then rank, then the full square
+    fn get_disambiguation(m: ChessMove, board: &BitBoard) -> String {
+        let others = board.get_legal_moves().into_iter()
+            .filter(|o| o.move_piece_type == m.move_piece_type && o.target_square == m.target_square && o.start_square != m.start_square)
+            .collect::<Vec<_>>();
+
+        if others.len() == 0 {
+            return "".to_owned();
+        }
+
+        if others.iter().all(|o| o.start_square.file() != m.start_square.file()) {
+            return m.start_square.file_char().to_string();
+        }
+
+        if others.iter().all(|o| o.start_square.rank() != m.start_square.rank()) {
+            return (m.start_square.rank() + 1).to_string();
+        }
+
+        return m.start_square.to_string();
+    }
+}
+
+pub fn parse_san(san: &str, board: &BitBoard) -> Result<ChessMove, SanError> {
+    let invalid = || SanError::InvalidSyntax(san.to_owned());
+
+    //Check, mate and annotation suffixes carry no information about the move itself
+    let body = san.trim().trim_end_matches(|c| c == '+' || c == '#' || c == '!' || c == '?');
+
+    if body.len() == 0 {
+        return Err(invalid());
+    }
+
+    let moves = board.get_legal_moves();
+
+    let castle_file = match body {
+        "O-O" | "0-0" => Some(6),
+        "O-O-O" | "0-0-0" => Some(2),
+        _ => None,
+    };
+
+    if let Some(file) = castle_file {
+        return moves.into_iter()
+            .find(|m| m.is_castle() && m.target_square.file() == file)
+            .ok_or(SanError::NoMatchingMove(san.to_owned()));
+    }
+
+    let mut chars = body.chars().filter(|c| *c != 'x' && *c != ':' && *c != '-').collect::<Vec<_>>();
+
+    if chars.len() == 0 {
+        return Err(invalid());
+    }
+
+    let piece_type = match chars[0] {
+        'N' => PieceType::Knight,
+        'B' => PieceType::Bishop,
+        'R' => PieceType::Rook,
+        'Q' => PieceType::Queen,
+        'K' => PieceType::King,
+        'a'..='h' => PieceType::Pawn,
+        _ => return Err(invalid()),
+    };
+
+    if piece_type != PieceType::Pawn {
+        chars.remove(0);
+    }
+
+    //Promotion as e8=Q or e8Q
+    let mut promotion = PieceType::None;
+    if let Some(last) = chars.last() {
+        let pt = match last {
+            'N' => PieceType::Knight,
+            'B' => PieceType::Bishop,
+            'R' => PieceType::Rook,
+            'Q' => PieceType::Queen,
+            _ => PieceType::None,
+        };
+
+        if pt != PieceType::None {
+            if piece_type != PieceType::Pawn {
+                return Err(invalid());
+            }
+
+            promotion = pt;
+            chars.pop();
+
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+    }
+
+    if chars.len() < 2 || chars.len() > 4 {
+        return Err(invalid());
+    }
+
+    let target_file = chars[chars.len() - 2];
+    let target_rank = chars[chars.len() - 1];
+
+    if !('a'..='h').contains(&target_file) || !('1'..='8').contains(&target_rank) {
+        return Err(invalid());
+    }
+
+    let target_square = Square::from_coords(target_file as u8 - b'a', target_rank as u8 - b'1');
+
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in &chars[..chars.len() - 2] {
+        match c {
+            'a'..='h' if from_file.is_none() && from_rank.is_none() => from_file = Some(*c as u8 - b'a'),
+            '1'..='8' if from_rank.is_none() => from_rank = Some(*c as u8 - b'1'),
+            _ => return Err(invalid()),
+        }
+    }
+
+    let candidates = moves.into_iter()
+        .filter(|m| PieceType::from_cpt(m.move_piece_type) == piece_type && m.target_square == target_square)
+        .filter(|m| !m.is_castle())
+        .filter(|m| PieceType::from_cpt(m.promotion_piece_type) == promotion)
+        .filter(|m| from_file.map_or(true, |f| m.start_square.file() == f))
+        .filter(|m| from_rank.map_or(true, |r| m.start_square.rank() == r))
+        .collect::<Vec<_>>();
+
+    return match candidates.len() {
+        0 => Err(SanError::NoMatchingMove(san.to_owned())),
+        1 => Ok(candidates[0]),
+        _ => Err(SanError::Ambiguous(san.to_owned())),
+    };
+}
@@ -0,0 +1,123 @@
+use std::io::{self, Write};
+
+use crate::{chess_move::ChessMove, karpfen_bot::CHECKMATE_VALUE, uci};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InfoScore {
+    Centipawns(i32),
+    //Moves until mate, negative when the side to move gets mated
+    Mate(i32),
+}
+
+//KarpfenBot units per centipawn, a middlegame pawn is worth 1000
+pub const CENTIPAWN_SCALE: i32 = 10;
+
+pub fn to_centipawns(score: i32) -> i32 {
+    return score / CENTIPAWN_SCALE;
+}
+
+impl InfoScore {
+    //Mates are CHECKMATE_VALUE minus the distance in plies, everything else is in KarpfenBot units
+    pub fn from_score(score: i32) -> InfoScore {
+        let mate_distance = CHECKMATE_VALUE - score.abs();
+
+        if mate_distance < 1000 {
+            let moves = (mate_distance + 1) / 2;
+            return InfoScore::Mate(if score > 0 { moves } else { -moves });
+        }
+
+        return InfoScore::Centipawns(to_centipawns(score));
+    }
+
+    pub fn to_uci(&self) -> String {
+        return match *self {
+            InfoScore::Centipawns(cp) => format!("cp {}", cp),
+            InfoScore::Mate(moves) => format!("mate {}", moves),
+        }
+    }
+}
+
+//Result of one finished iteration
+#[derive(Clone)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub seldepth: u8,
+    //Rank of the line, starting at 1
+    pub multipv: usize,
+    pub score: InfoScore,
+    pub nodes: u64,
+    //Milliseconds since the search started
+    pub time: u64,
+    pub hashfull: Option<u32>,
+    pub pv: Vec<ChessMove>,
+}
+
+impl SearchInfo {
+    pub fn get_nps(&self) -> u64 {
+        return self.nodes * 1000 / self.time.max(1);
+    }
+
+    //Without the leading "info"
+    pub fn to_uci(&self) -> String {
+        let mut res = format!("depth {} seldepth {} multipv {} score {} nodes {} nps {} time {}", self.depth, self.seldepth, self.multipv, self.score.to_uci(), self.nodes, self.get_nps(), self.time);
+
+        if let Some(hashfull) = self.hashfull {
+            res += &format!(" hashfull {}", hashfull);
+        }
+
+        //Nothing after a null move is a legal continuation
+        let pv = self.pv.iter().take_while(|m| !m.is_null_move()).collect::<Vec<_>>();
+
+        if pv.len() > 0 {
+            res += " pv";
+
+            for m in pv {
+                res += " ";
+                res += &uci::get_uci_name(*m);
+            }
+        }
+
+        return res;
+    }
+}
+
+//Gets every finished iteration of the main search thread
+pub trait SearchReporter {
+    fn report(&mut self, info: &SearchInfo);
+}
+
+pub struct SilentReporter;
+
+impl SearchReporter for SilentReporter {
+    fn report(&mut self, _info: &SearchInfo) {}
+}
+
+pub struct UciReporter;
+
+impl SearchReporter for UciReporter {
+    fn report(&mut self, info: &SearchInfo) {
+        println!("info {}", info.to_uci());
+        io::stdout().flush().unwrap();
+    }
+}
+
+//Collects every reported info, e.g. for match logs
+pub struct InfoLog {
+    pub infos: Vec<SearchInfo>,
+}
+
+impl InfoLog {
+    pub fn new() -> InfoLog {
+        return InfoLog { infos: Vec::new() };
+    }
+
+    pub fn get_last(&self) -> Option<&SearchInfo> {
+        return self.infos.last();
+    }
+}
+
+impl SearchReporter for InfoLog {
+    fn report(&mut self, info: &SearchInfo) {
+        self.infos.push(info.clone());
+    }
+}
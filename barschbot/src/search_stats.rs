@@ -1,24 +1,80 @@
+use crate::move_picker::{self, STAGE_COUNT};
+
 pub struct SearchStats {
     pub nodes: u64,
     pub qs: u64,
+    //Highest ply reached, quiescence included
+    pub seldepth: u8,
     pub best_move_hits: u64,
     pub not_best_move_hits: u64,
     pub null_move_prunes: u64,
+    //Null move cutoffs a verification search did not confirm
+    pub null_move_refutations: u64,
+    pub reverse_futility_prunes: u64,
+    pub razor_prunes: u64,
+    pub futility_prunes: u64,
+    pub late_move_prunes: u64,
+    pub late_move_reductions: u64,
+    //Reduced or null window searches that beat alpha and had to be repeated
+    pub re_searches: u64,
+    //Beta cutoffs per move picker stage, indexed by PickerStage
+    pub stage_cutoffs: [u64; STAGE_COUNT],
 }
 
 impl SearchStats {
     pub fn new() -> SearchStats {
-        return SearchStats { nodes: 0, qs: 0, best_move_hits: 0, not_best_move_hits: 0, null_move_prunes: 0 };
+        return SearchStats { nodes: 0, qs: 0, seldepth: 0, best_move_hits: 0, not_best_move_hits: 0, null_move_prunes: 0, null_move_refutations: 0, reverse_futility_prunes: 0, razor_prunes: 0, futility_prunes: 0, late_move_prunes: 0,
+            late_move_reductions: 0, re_searches: 0, stage_cutoffs: [0; STAGE_COUNT] };
     }
 
     pub fn reset(&mut self) {
         self.nodes = 0;
         self.qs = 0;
+        self.seldepth = 0;
         self.best_move_hits = 0;
         self.not_best_move_hits = 0;
         self.null_move_prunes = 0;
+        self.null_move_refutations = 0;
+        self.reverse_futility_prunes = 0;
+        self.razor_prunes = 0;
+        self.futility_prunes = 0;
+        self.late_move_prunes = 0;
+        self.late_move_reductions = 0;
+        self.re_searches = 0;
+        self.stage_cutoffs = [0; STAGE_COUNT];
+    }
+    //Adds the counters of a helper thread
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.qs += other.qs;
+        self.seldepth = self.seldepth.max(other.seldepth);
+        self.best_move_hits += other.best_move_hits;
+        self.not_best_move_hits += other.not_best_move_hits;
+        self.null_move_prunes += other.null_move_prunes;
+        self.null_move_refutations += other.null_move_refutations;
+        self.reverse_futility_prunes += other.reverse_futility_prunes;
+        self.razor_prunes += other.razor_prunes;
+        self.futility_prunes += other.futility_prunes;
+        self.late_move_prunes += other.late_move_prunes;
+        self.late_move_reductions += other.late_move_reductions;
+        self.re_searches += other.re_searches;
+
+        for i in 0..STAGE_COUNT {
+            self.stage_cutoffs[i] += other.stage_cutoffs[i];
+        }
     }
+
     pub fn print(&self) {
-        println!("Nodes: {} Qs: {} BMFM ratio: {} NMP: {}", self.nodes, self.qs, self.best_move_hits as f32 / (self.not_best_move_hits + self.best_move_hits) as f32, self.null_move_prunes);
+        println!("Nodes: {} Qs: {} BMFM ratio: {} NMP: {} LMR: {} Re-searches: {}", self.nodes, self.qs, self.best_move_hits as f32 / (self.not_best_move_hits + self.best_move_hits) as f32,
+            self.null_move_prunes, self.late_move_reductions, self.re_searches);
+        println!("RFP: {} Razoring: {} Futility: {} LMP: {} Refuted null moves: {}", self.reverse_futility_prunes, self.razor_prunes, self.futility_prunes, self.late_move_prunes, self.null_move_refutations);
+
+        let total = self.stage_cutoffs.iter().sum::<u64>();
+        if total > 0 {
+            for stage in move_picker::ALL_STAGES {
+                let cutoffs = self.stage_cutoffs[stage as usize];
+                println!("\t{}: {} cutoffs ({:.1}%)", stage.to_string(), cutoffs, cutoffs as f64 * 100.0 / total as f64);
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,214 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Instant};
+
+//Time the GUI and the pipes need per move, kept off the clock
+const MOVE_OVERHEAD: u64 = 20;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+//The clock is only read every CHECK_INTERVAL nodes
+const CHECK_INTERVAL: u64 = 1024;
+
+//Limits of a single search as given by "go", times in milliseconds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: u64,
+    pub binc: u64,
+    pub movestogo: Option<u64>,
+    pub movetime: Option<u64>,
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    //Stop once a mate in this many moves is found
+    pub mate: Option<u32>,
+    pub infinite: bool,
+    //Search without limits until ponderhit, the clock only starts then
+    pub ponder: bool,
+    //Keep deepening past depth until this much time has passed, see KBSettings::min_search_time
+    pub min_time: u64,
+}
+
+impl SearchLimits {
+    pub fn new() -> SearchLimits {
+        return SearchLimits { wtime: None, btime: None, winc: 0, binc: 0, movestogo: None, movetime: None, depth: None, nodes: None, mate: None, infinite: false, ponder: false, min_time: 0 };
+    }
+
+    //The old max_depth / min_search_time behaviour of both bots
+    pub fn from_depth(depth: u8, min_time: u64) -> SearchLimits {
+        let mut limits = SearchLimits::new();
+        limits.depth = Some(depth);
+        limits.min_time = min_time;
+
+        return limits;
+    }
+
+    pub fn is_limited(&self) -> bool {
+        return self.wtime.is_some() || self.btime.is_some() || self.movetime.is_some() || self.depth.is_some()
+            || self.nodes.is_some() || self.mate.is_some() || self.infinite;
+    }
+}
+
+//Lets another thread stop a running search or tell it that the ponder move was played
+#[derive(Clone)]
+pub struct SearchHandle {
+    stop: Arc<AtomicBool>,
+    pondering: Arc<AtomicBool>,
+}
+
+impl SearchHandle {
+    pub fn new() -> SearchHandle {
+        return SearchHandle { stop: Arc::new(AtomicBool::new(false)), pondering: Arc::new(AtomicBool::new(false)) };
+    }
+
+    //Has to be called before the search starts, a stop or ponderhit sent before that would get lost otherwise
+    pub fn reset(&self, ponder: bool) {
+        self.stop.store(false, Ordering::Relaxed);
+        self.pondering.store(ponder, Ordering::Relaxed);
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn ponderhit(&self) {
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_stop_requested(&self) -> bool {
+        return self.stop.load(Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        return self.pondering.load(Ordering::Relaxed);
+    }
+}
+
+//Turns the limits into a soft limit, checked before every new iteration, and a hard limit the search is aborted at
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<u64>,
+    hard_limit: Option<u64>,
+    max_depth: Option<u8>,
+    max_nodes: Option<u64>,
+    mate: Option<u32>,
+    min_time: u64,
+    stopped: bool,
+    //Only set while a ponder search waits for ponderhit
+    ponder_handle: Option<SearchHandle>,
+    handle: Option<SearchHandle>,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, white: bool) -> TimeManager {
+        let mut soft_limit = None;
+        let mut hard_limit = None;
+
+        let (time_left, increment) = if white { (limits.wtime, limits.winc) } else { (limits.btime, limits.binc) };
+
+        if let Some(movetime) = limits.movetime {
+            let limit = movetime.saturating_sub(MOVE_OVERHEAD).max(1);
+            soft_limit = Some(limit);
+            hard_limit = Some(limit);
+        }
+        else if let Some(time_left) = time_left {
+            let available = time_left.saturating_sub(MOVE_OVERHEAD).max(1);
+            let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+            //An iteration that started before the soft limit may run up to four times as long, but never eats most of the clock
+            let hard = (available * 3 / 4).max(1);
+            let soft = (available / moves_to_go + increment * 3 / 4).min(hard);
+
+            soft_limit = Some(soft);
+            hard_limit = Some((soft * 4).min(hard));
+        }
+
+        if limits.infinite {
+            return TimeManager { start: Instant::now(), soft_limit: None, hard_limit: None, max_depth: None, max_nodes: None, mate: None, min_time: 0, stopped: false, ponder_handle: None, handle: None };
+        }
+
+        return TimeManager { start: Instant::now(), soft_limit, hard_limit, max_depth: limits.depth, max_nodes: limits.nodes, mate: limits.mate, min_time: limits.min_time, stopped: false, ponder_handle: None, handle: None };
+    }
+
+    //Stops on handle.stop(), a ponder search also waits for handle.ponderhit() before any limit counts
+    pub fn with_handle(mut self, handle: SearchHandle, ponder: bool) -> TimeManager {
+        if ponder {
+            self.ponder_handle = Some(handle.clone());
+        }
+
+        self.handle = Some(handle);
+        return self;
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        return self.ponder_handle.is_some();
+    }
+
+    //Reads the handle, on ponderhit the clock starts over since only now the engine's own time runs
+    fn update_handle(&mut self) {
+        if self.handle.as_ref().is_some_and(|handle| handle.is_stop_requested()) {
+            self.stopped = true;
+        }
+
+        if self.ponder_handle.as_ref().is_some_and(|handle| !handle.is_pondering()) {
+            self.ponder_handle = None;
+            self.start = Instant::now();
+        }
+    }
+
+    pub fn get_elapsed(&self) -> u64 {
+        return self.start.elapsed().as_millis() as u64;
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        return self.stopped;
+    }
+
+    //Called on every node, true once the search has to be aborted
+    pub fn check(&mut self, nodes: u64) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if nodes % CHECK_INTERVAL == 0 {
+            self.update_handle();
+        }
+
+        if self.stopped || self.is_pondering() {
+            return self.stopped;
+        }
+
+        if self.max_nodes.is_some_and(|max| nodes >= max) {
+            self.stopped = true;
+        }
+
+        if nodes % CHECK_INTERVAL == 0 && self.hard_limit.is_some_and(|limit| self.get_elapsed() >= limit) {
+            self.stopped = true;
+        }
+
+        return self.stopped;
+    }
+
+    //mate_plies is the distance to a mate for the side to move, if the last iteration found one
+    pub fn should_start_iteration(&self, completed_depth: u8, mate_plies: Option<u32>) -> bool {
+        if self.stopped || self.handle.as_ref().is_some_and(|handle| handle.is_stop_requested()) {
+            return false;
+        }
+
+        let elapsed = match &self.ponder_handle {
+            Some(handle) if handle.is_pondering() => return true,
+            //Ponderhit came in after the last clock check, the engine's time only just started
+            Some(_) => 0,
+            None => self.get_elapsed(),
+        };
+
+        if self.max_depth.is_some_and(|depth| completed_depth >= depth) && elapsed >= self.min_time {
+            return false;
+        }
+
+        if let (Some(mate), Some(plies)) = (self.mate, mate_plies) {
+            if plies < 2 * mate {
+                return false;
+            }
+        }
+
+        return !self.soft_limit.is_some_and(|limit| elapsed >= limit);
+    }
+}
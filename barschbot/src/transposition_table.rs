@@ -0,0 +1,245 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::{bit_board::BitBoard, chess_move::{self, ChessMove}, colored_piece_type::ColoredPieceType, karpfen_bot::CHECKMATE_VALUE, square::Square};
+
+pub const DEFAULT_HASH_SIZE: usize = 16;
+pub const MAX_HASH_SIZE: usize = 65536;
+
+const BUCKET_SIZE: usize = 4;
+const AGE_COUNT: u8 = 64;
+//Scores this close to CHECKMATE_VALUE are mates, they get stored relative to the node instead of the root
+const MATE_THRESHOLD: i32 = CHECKMATE_VALUE - 1000;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum NodeType {
+    Exact,
+    LowerBound,
+    UpperBound,
+    Unknown,
+}
+
+impl NodeType {
+    fn from_u8(value: u8) -> NodeType {
+        return match value {
+            1 => NodeType::Exact,
+            2 => NodeType::LowerBound,
+            3 => NodeType::UpperBound,
+            _ => NodeType::Unknown,
+        }
+    }
+
+    fn to_u8(&self) -> u8 {
+        return match *self {
+            NodeType::Exact => 1,
+            NodeType::LowerBound => 2,
+            NodeType::UpperBound => 3,
+            NodeType::Unknown => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TTEntry {
+    pub depth: u8,
+    pub score: i32,
+    pub best_move: ChessMove,
+    pub node_type: NodeType,
+}
+
+pub const EMPTY_ENTRY: TTEntry = TTEntry { depth: 0, score: 0, best_move: chess_move::NULL_MOVE, node_type: NodeType::Unknown };
+
+//Every entry is two words, the key xor the data and the data itself. The data packs
+//move (16 bit) | score (32 bit) | depth (8 bit) | node type (2 bit) | age (6 bit)
+//Threads read and write without locking, a torn entry fails the key check and counts as a miss
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+    bucket_mask: usize,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        let bytes = size_mb.clamp(1, MAX_HASH_SIZE) * 1024 * 1024;
+        let max_buckets = bytes / (BUCKET_SIZE * std::mem::size_of::<[AtomicU64; 2]>());
+
+        //Largest power of two that fits, so the bucket index is a mask
+        let bucket_count = 1_usize << (usize::BITS - 1 - max_buckets.leading_zeros());
+        let entries = (0..bucket_count * BUCKET_SIZE).map(|_| [AtomicU64::new(0), AtomicU64::new(0)]).collect();
+
+        return TranspositionTable { entries, bucket_mask: bucket_count - 1, age: AtomicU8::new(0) };
+    }
+
+    pub fn get_size_mb(&self) -> usize {
+        return self.entries.len() * std::mem::size_of::<[AtomicU64; 2]>() / (1024 * 1024);
+    }
+
+    pub fn clear(&self) {
+        for entry in &self.entries {
+            entry[0].store(0, Ordering::Relaxed);
+            entry[1].store(0, Ordering::Relaxed);
+        }
+
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    //Called once per search, entries from older searches get replaced first
+    pub fn new_search(&self) {
+        self.age.store((self.get_current_age() + 1) % AGE_COUNT, Ordering::Relaxed);
+    }
+
+    fn get_current_age(&self) -> u8 {
+        return self.age.load(Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, board: &BitBoard, ply: i8) -> Option<TTEntry> {
+        let key = board.get_zoberist_hash();
+
+        for entry in self.get_bucket(key) {
+            let data = entry[1].load(Ordering::Relaxed);
+
+            if entry[0].load(Ordering::Relaxed) ^ data != key || get_node_type(data) == NodeType::Unknown {
+                continue;
+            }
+
+            return Some(TTEntry {
+                depth: get_depth(data),
+                score: score_from_tt(get_score(data), ply),
+                best_move: decode_move(board, data as u16),
+                node_type: get_node_type(data),
+            });
+        }
+
+        return None;
+    }
+
+    pub fn store(&self, board: &BitBoard, ply: i8, depth: u8, score: i32, mut best_move: ChessMove, node_type: NodeType) {
+        let key = board.get_zoberist_hash();
+        let bucket = self.get_bucket(key);
+
+        let mut replace = 0;
+        let mut replace_value = i32::MAX;
+
+        for i in 0..BUCKET_SIZE {
+            let data = bucket[i][1].load(Ordering::Relaxed);
+
+            if bucket[i][0].load(Ordering::Relaxed) ^ data == key {
+                let old_type = get_node_type(data);
+
+                //Keep a deeper result or an exact score over a bound, but entries from old searches always go
+                if get_age(data) == self.get_current_age() && old_type != NodeType::Unknown && (depth < get_depth(data) ||
+                    (old_type == NodeType::Exact && node_type != NodeType::Exact)) {
+                    return;
+                }
+
+                if best_move.is_null_move() {
+                    best_move = decode_move(board, data as u16);
+                }
+
+                replace = i;
+                break;
+            }
+
+            //Shallow entries from old searches are the cheapest to lose
+            let value = if get_node_type(data) == NodeType::Unknown { i32::MIN }
+                else { get_depth(data) as i32 - 8 * self.get_relative_age(data) as i32 };
+
+            if value < replace_value {
+                replace_value = value;
+                replace = i;
+            }
+        }
+
+        let data = encode_move(best_move) as u64
+            | ((score_to_tt(score, ply) as u32 as u64) << 16)
+            | ((depth as u64) << 48)
+            | ((node_type.to_u8() as u64) << 56)
+            | ((self.get_current_age() as u64) << 58);
+
+        bucket[replace][0].store(key ^ data, Ordering::Relaxed);
+        bucket[replace][1].store(data, Ordering::Relaxed);
+    }
+
+    //Permille of the first 1000 entries written during the current search
+    pub fn get_hashfull(&self) -> u32 {
+        let sample = self.entries.len().min(1000);
+
+        let used = self.entries[..sample].iter()
+            .map(|entry| entry[1].load(Ordering::Relaxed))
+            .filter(|data| get_node_type(*data) != NodeType::Unknown && get_age(*data) == self.get_current_age())
+            .count();
+
+        return (used * 1000 / sample) as u32;
+    }
+
+    fn get_bucket(&self, key: u64) -> &[[AtomicU64; 2]] {
+        let index = (key as usize & self.bucket_mask) * BUCKET_SIZE;
+        return &self.entries[index..index + BUCKET_SIZE];
+    }
+
+    fn get_relative_age(&self, data: u64) -> u8 {
+        return (self.get_current_age() + AGE_COUNT - get_age(data)) % AGE_COUNT;
+    }
+}
+
+fn get_score(data: u64) -> i32 {
+    return (data >> 16) as u32 as i32;
+}
+
+fn get_depth(data: u64) -> u8 {
+    return (data >> 48) as u8;
+}
+
+fn get_node_type(data: u64) -> NodeType {
+    return NodeType::from_u8(((data >> 56) & 3) as u8);
+}
+
+fn get_age(data: u64) -> u8 {
+    return (data >> 58) as u8;
+}
+
+//Mates are stored as distance from the current node and turned back into distance from the root on probe
+fn score_to_tt(score: i32, ply: i8) -> i32 {
+    if score > MATE_THRESHOLD {
+        return score + ply as i32;
+    }
+
+    if score < -MATE_THRESHOLD {
+        return score - ply as i32;
+    }
+
+    return score;
+}
+
+fn score_from_tt(score: i32, ply: i8) -> i32 {
+    if score > MATE_THRESHOLD {
+        return score - ply as i32;
+    }
+
+    if score < -MATE_THRESHOLD {
+        return score + ply as i32;
+    }
+
+    return score;
+}
+
+//start (6 bit) | target (6 bit) | promotion piece (4 bit), 0 is the null move
+fn encode_move(m: ChessMove) -> u16 {
+    if m.is_null_move() {
+        return 0;
+    }
+
+    return m.start_square as u16 | ((m.target_square as u16) << 6) | ((m.promotion_piece_type as u16) << 12);
+}
+
+//The piece types come from the board, the move picker still checks that the move fits it
+fn decode_move(board: &BitBoard, value: u16) -> ChessMove {
+    if value == 0 {
+        return chess_move::NULL_MOVE;
+    }
+
+    let start_square = Square::from_u8((value & 63) as u8);
+    let target_square = Square::from_u8(((value >> 6) & 63) as u8);
+    let promotion_piece_type = ColoredPieceType::from_u8((value >> 12) as u8);
+
+    return ChessMove::new_pawn_move(start_square, target_square, board.get_piece_type(start_square), board.get_piece_type(target_square), promotion_piece_type);
+}
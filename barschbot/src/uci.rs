@@ -0,0 +1,306 @@
+use std::{collections::VecDeque, io::{self, BufRead, Write}, sync::mpsc::{self, Receiver, RecvTimeoutError}, thread, time::Duration};
+
+use crate::{chess_move::{self, ChessMove}, endgame_table::EndgameTable, game::{Game, GameState}, karpfen_bot::KarpfenBot, kb_settings::{self, KBSettings}, opening_book::OpeningBook, search_info::UciReporter, time_manager::SearchLimits, transposition_table};
+
+const ENGINE_NAME: &str = "KarpfenBot";
+const ENGINE_AUTHOR: &str = "Bobitsmagic";
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+//The search recurses deep, the main thread's default stack is not guaranteed on the spawned one
+const SEARCH_STACK_SIZE: usize = 16 * 1024 * 1024;
+//How often the input is polled while a search runs
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+pub fn uci_loop(bot: &mut KarpfenBot, opening_book: &OpeningBook, endgame_table: &EndgameTable) {
+    let input = spawn_input_reader();
+    let empty_book = OpeningBook::new();
+
+    let mut game = Game::get_start_position();
+    let mut own_book = true;
+    //Commands that came in during a search and wait for it to finish
+    let mut pending = VecDeque::new();
+
+    loop {
+        let line = match pending.pop_front() {
+            Some(line) => line,
+            None => match input.recv() {
+                Ok(line) => line,
+                Err(_) => break,
+            },
+        };
+
+        let parts = line.split_whitespace().collect::<Vec<_>>();
+
+        if parts.len() == 0 {
+            continue;
+        }
+
+        match parts[0] {
+            "uci" => {
+                let settings = bot.get_settings();
+
+                println!("id name {}", ENGINE_NAME);
+                println!("id author {}", ENGINE_AUTHOR);
+                println!("option name Hash type spin default {} min 1 max {}", transposition_table::DEFAULT_HASH_SIZE, transposition_table::MAX_HASH_SIZE);
+                println!("option name Threads type spin default {} min 1 max {}", settings.thread_count, kb_settings::MAX_THREAD_COUNT);
+                println!("option name MultiPV type spin default {} min 1 max {}", settings.multi_pv, kb_settings::MAX_MULTI_PV);
+                println!("option name Ponder type check default false");
+                println!("option name OwnBook type check default {}", own_book);
+                println!("option name MaxDepth type spin default {} min 1 max 64", settings.max_depth);
+                println!("option name MinSearchTime type spin default {} min 0 max 3600000", settings.min_search_time);
+                println!("uciok");
+            },
+
+            "isready" => println!("readyok"),
+
+            "ucinewgame" => {
+                bot.reset();
+                game = Game::get_start_position();
+            },
+
+            "position" => {
+                match parse_position(&parts[1..]) {
+                    Some(g) => game = g,
+                    None => println!("info string invalid position: {}", line),
+                }
+            },
+
+            "go" => {
+                let book = if own_book { opening_book } else { &empty_book };
+
+                if !go(bot, &mut game, &parts[1..], book, endgame_table, &input, &mut pending) {
+                    break;
+                }
+            },
+
+            //Only meaningful while a search runs, go handles them there
+            "stop" | "ponderhit" => (),
+
+            "setoption" => set_option(bot, &mut own_book, &parts[1..]),
+
+            "d" => game.get_board().print(),
+
+            "quit" => break,
+
+            _ => println!("info string unknown command: {}", parts[0]),
+        }
+
+        io::stdout().flush().unwrap();
+    }
+}
+
+pub fn get_uci_name(m: ChessMove) -> String {
+    if m.is_null_move() {
+        return "0000".to_owned();
+    }
+
+    return m.get_uci();
+}
+
+//position [startpos | fen <fen>] [moves <m1> ... <mn>]
+pub fn parse_position(args: &[&str]) -> Option<Game> {
+    if args.len() == 0 {
+        return None;
+    }
+
+    let moves_index = args.iter().position(|s| *s == "moves").unwrap_or(args.len());
+
+    let fen = match args[0] {
+        "startpos" => START_FEN.to_owned(),
+        "fen" => args[1..moves_index].join(" "),
+        _ => return None,
+    };
+
+    let mut game = match Game::try_from_fen(&fen) {
+        Ok(game) => game,
+        Err(e) => {
+            println!("info string invalid fen: {}", e);
+            return None;
+        }
+    };
+
+    for uci in args.iter().skip(moves_index + 1) {
+        //The GUI decides when the game is over, so keep following its moves on a fresh history
+        if game.get_game_state() != GameState::Undecided {
+            game = Game::from_board_with_clocks(game.get_board(), game.fifty_move_counter(), game.get_fullmove_number());
+        }
+
+        let m = game.get_uci_move(uci.to_string());
+
+        if m == chess_move::NULL_MOVE {
+            return None;
+        }
+
+        game.make_move(m);
+    }
+
+    return Some(game);
+}
+
+//Reads stdin on its own thread, so stop and ponderhit get through while the search runs
+fn spawn_input_reader() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            match line {
+                Ok(line) => if sender.send(line).is_err() { break; },
+                Err(_) => break,
+            }
+        }
+    });
+
+    return receiver;
+}
+
+//Searches on a second thread while the input keeps getting read. Prints bestmove and returns false on quit
+fn go(bot: &mut KarpfenBot, game: &mut Game, args: &[&str], opening_book: &OpeningBook, endgame_table: &EndgameTable,
+    input: &Receiver<String>, pending: &mut VecDeque<String>) -> bool {
+    let mut limits = parse_go(args);
+
+    //A plain "go" searches like the bot was configured
+    if !limits.is_limited() && !limits.ponder {
+        let settings = bot.get_settings();
+        limits = SearchLimits::from_depth(settings.max_depth, settings.min_search_time);
+    }
+
+    let handle = bot.get_search_handle();
+    handle.reset(limits.ponder);
+
+    //bestmove may only be sent after stop for infinite searches, and after stop or ponderhit while pondering
+    let mut must_wait = limits.infinite || limits.ponder;
+    let mut input_open = true;
+    let mut quit = false;
+
+    let (m, ponder_move) = thread::scope(|scope| {
+        let search = thread::Builder::new().stack_size(SEARCH_STACK_SIZE).spawn_scoped(scope, || {
+            let m = bot.get_best_move_with_limits(game, opening_book, endgame_table, &limits, &mut UciReporter);
+            return (m, bot.get_ponder_move(game, m));
+        }).unwrap();
+
+        while !search.is_finished() || must_wait {
+            if !input_open {
+                //Nobody is left to send stop
+                if must_wait {
+                    handle.stop();
+                    must_wait = false;
+                }
+
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            match input.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => match line.trim() {
+                    "stop" => {
+                        handle.stop();
+                        must_wait = false;
+                    },
+                    //The expected move was played, the search goes on as a normal timed search
+                    "ponderhit" => {
+                        handle.ponderhit();
+                        must_wait = limits.infinite;
+                    },
+                    "isready" => println!("readyok"),
+                    //Commands sent before quit still get their turn
+                    "quit" if pending.len() > 0 => pending.push_back(line),
+                    "quit" => {
+                        handle.stop();
+                        must_wait = false;
+                        quit = true;
+                    },
+                    _ => pending.push_back(line),
+                },
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => input_open = false,
+            }
+
+            io::stdout().flush().unwrap();
+        }
+
+        return search.join().unwrap();
+    });
+
+    if ponder_move.is_null_move() {
+        println!("bestmove {}", get_uci_name(m));
+    }
+    else {
+        println!("bestmove {} ponder {}", get_uci_name(m), get_uci_name(ponder_move));
+    }
+
+    return !quit;
+}
+
+//go [ponder] [wtime <x>] [btime <x>] [winc <x>] [binc <x>] [movestogo <x>] [movetime <x>] [depth <x>] [nodes <x>] [mate <x>] [infinite]
+pub fn parse_go(args: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::new();
+
+    for i in 0..args.len() {
+        let value = args.get(i + 1).and_then(|s| s.parse::<u64>().ok());
+
+        match (args[i], value) {
+            ("wtime", Some(v)) => limits.wtime = Some(v),
+            ("btime", Some(v)) => limits.btime = Some(v),
+            ("winc", Some(v)) => limits.winc = v,
+            ("binc", Some(v)) => limits.binc = v,
+            ("movestogo", Some(v)) => limits.movestogo = Some(v.max(1)),
+            ("movetime", Some(v)) => limits.movetime = Some(v),
+            ("depth", Some(v)) => limits.depth = Some(v.clamp(1, 64) as u8),
+            ("nodes", Some(v)) => limits.nodes = Some(v),
+            ("mate", Some(v)) => limits.mate = Some(v.min(u32::MAX as u64) as u32),
+            ("infinite", _) => limits.infinite = true,
+            ("ponder", _) => limits.ponder = true,
+            _ => (),
+        }
+    }
+
+    return limits;
+}
+
+fn set_option(bot: &mut KarpfenBot, own_book: &mut bool, args: &[&str]) {
+    //setoption name <id> [value <x>]
+    let value_index = args.iter().position(|s| *s == "value").unwrap_or(args.len());
+
+    if args.len() < 2 || args[0] != "name" {
+        return;
+    }
+
+    let name = args[1..value_index].join(" ");
+    let value = args.iter().skip(value_index + 1).map(|s| *s).collect::<Vec<_>>().join(" ");
+
+    let mut settings: KBSettings = bot.get_settings();
+
+    match name.to_lowercase().as_str() {
+        "hash" => {
+            if let Ok(v) = value.parse::<usize>() {
+                bot.set_hash_size(v);
+            }
+        },
+        "threads" => {
+            if let Ok(v) = value.parse::<usize>() {
+                settings.thread_count = v.clamp(1, kb_settings::MAX_THREAD_COUNT);
+            }
+        },
+        "multipv" => {
+            if let Ok(v) = value.parse::<usize>() {
+                settings.multi_pv = v.clamp(1, kb_settings::MAX_MULTI_PV);
+            }
+        },
+        "ownbook" => *own_book = value == "true",
+        //Pondering is driven by go ponder and ponderhit, the option only tells the GUI it may use them
+        "ponder" => (),
+        "maxdepth" => {
+            if let Ok(v) = value.parse::<u8>() {
+                settings.max_depth = v.clamp(1, 64);
+            }
+        },
+        "minsearchtime" => {
+            if let Ok(v) = value.parse::<u64>() {
+                settings.min_search_time = v;
+            }
+        },
+        _ => println!("info string unknown option: {}", name),
+    }
+
+    bot.set_settings(settings);
+}
@@ -14,11 +14,11 @@ use piston::input::{RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
 use piston::window::WindowSettings;
 use piston_window::*;
 
-use crate::chess_move::ChessMove;
-use crate::colored_piece_type::ColoredPieceType;
-use crate::game::Game;
-use crate::square::Square;
-use crate::zoberist_hash;
+use barschbot::chess_move::ChessMove;
+use barschbot::colored_piece_type::ColoredPieceType;
+use barschbot::game::Game;
+use barschbot::square::Square;
+use barschbot::zoberist_hash;
 use graphics::draw_state::DrawState;
 
 pub struct Visualizer {
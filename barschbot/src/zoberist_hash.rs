@@ -1,4 +1,4 @@
-use crate::{constants, chess_move::{self, ChessMove}, square::Square, colored_piece_type::ColoredPieceType, piece_type::PieceType};
+use crate::{square::Square, colored_piece_type::ColoredPieceType};
 
 const SQUARE_PIECE_HASHS: [[u64; 12]; 64] = [
 [3039665143350635744, 17092169764834922902, 3925853326203578338, 17354356390057816443, 7472514735885487017, 15392575389892135373, 6651258979722590487, 7954050523632553952, 4091066645749342542, 7367789944430992549, 11178490497920601604, 15053050127913984131],
@@ -124,42 +124,31 @@ impl ZoberistHash64 {
         return  hash;
     }
 
-    pub fn update_hash(&mut self, m: ChessMove, old_ep_square: u8,  wqc: bool, wkc: bool, bqc: bool, bkc: bool) {
-        self.value ^= SQUARE_PIECE_HASHS[m.start_square as usize][m.move_piece_type as usize];
-        self.value ^= SQUARE_PIECE_HASHS[m.target_square as usize][m.move_piece_type as usize];
+    //All toggles are their own inverse, BitBoard calls them once with the old and once with the new state
+    pub fn toggle_piece(&mut self, colored_piece_type: ColoredPieceType, square: Square) {
+        self.value ^= SQUARE_PIECE_HASHS[square as usize][colored_piece_type as usize];
+    }
 
-        if m.capture_piece_type != ColoredPieceType::None {
-            self.value ^= SQUARE_PIECE_HASHS[m.target_square as usize][m.capture_piece_type as usize];
-        }
+    pub fn toggle_turn(&mut self) {
+        self.value ^= TURN_HASH;
+    }
 
-        if (m.start_square == Square::A1 || m.target_square == Square::A1) && wqc {
+    pub fn toggle_en_passant(&mut self, ep_square: Square) {
+        self.value ^= ep_square as u64 * EP_HASH;
+    }
+
+    pub fn toggle_castling(&mut self, wqc: bool, wkc: bool, bqc: bool, bkc: bool) {
+        if wqc {
             self.value ^= WQC_HASH;
         }
-        if (m.start_square == Square::H1 || m.target_square == Square::H1) && wkc {
+        if wkc {
             self.value ^= WKC_HASH;
         }
-        if (m.start_square == Square::A8 || m.target_square == Square::A8) && bqc {
+        if bqc {
             self.value ^= BQC_HASH;
         }
-        if (m.start_square == Square::H8 || m.target_square == Square::H8) && bkc {
+        if bkc {
             self.value ^= BKC_HASH;
         }
-
-        self.value ^= TURN_HASH;
-
-        self.value ^= old_ep_square as u64 * EP_HASH;
-
-        let pawn_direction: i32 = if m.is_white_move() { 1 } else { -1 };
-        let mut ep = 0;
-        //double pawn move
-        if PieceType::from_cpt(m.move_piece_type) == PieceType::Pawn 
-            &&  (m.start_square as u8).abs_diff(m.target_square as u8) == 16 {
-            ep = (m.target_square as i32 - pawn_direction * 8) as u8;
-        }
-        else {
-            ep = constants::NO_SQUARE;
-        }
-
-        self.value ^= ep as u64 * EP_HASH;
     }
-}
\ No newline at end of file
+}
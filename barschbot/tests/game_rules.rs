@@ -0,0 +1,213 @@
+use barschbot::{bit_board::BitBoard, chess_move::ChessMove, game::{Game, GameState}};
+
+fn find_move(board: &BitBoard, uci: &str) -> Option<ChessMove> {
+    return board.get_legal_moves().into_iter().find(|m| m.get_uci() == uci);
+}
+
+fn play(board: &mut BitBoard, moves: &[&str]) {
+    for uci in moves {
+        let m = find_move(board, uci).unwrap_or_else(|| panic!("{} is not legal in {}", uci, board.get_fen()));
+        board.make_move(m);
+    }
+}
+
+#[test]
+fn fen_round_trip() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w Kq d6 0 3",
+        "8/8/8/3k4/8/8/8/4K3 b - - 37 90",
+    ];
+
+    for fen in fens {
+        assert_eq!(Game::from_fen(fen).get_fen(), fen);
+    }
+}
+
+#[test]
+fn invalid_fens() {
+    let fens = [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+    ];
+
+    for fen in fens {
+        assert!(Game::try_from_fen(fen).is_err(), "{}", fen);
+    }
+}
+
+#[test]
+fn make_undo_symmetry() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ];
+
+    for fen in fens {
+        let mut game = Game::from_fen(fen);
+        let start_hash = game.get_board().get_zoberist_hash();
+
+        for m in game.get_legal_moves() {
+            game.make_move(m);
+
+            for reply in game.get_legal_moves() {
+                let before = game.get_fen();
+                let hash = game.get_board().get_zoberist_hash();
+
+                game.make_move(reply);
+                game.undo_move();
+
+                assert_eq!(game.get_fen(), before);
+                assert_eq!(game.get_board().get_zoberist_hash(), hash);
+            }
+
+            game.undo_move();
+        }
+
+        assert_eq!(game.get_fen(), fen);
+        assert_eq!(game.get_board().get_zoberist_hash(), start_hash);
+        assert_eq!(game.move_depth(), 0);
+    }
+}
+
+#[test]
+fn transpositions_share_hash() {
+    let mut a = BitBoard::start_position();
+    let mut b = BitBoard::start_position();
+
+    play(&mut a, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+    play(&mut b, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+    assert_eq!(a.get_zoberist_hash(), b.get_zoberist_hash());
+
+    play(&mut a, &["f3g1", "f6g8", "c3b1", "c6b8"]);
+    assert_eq!(a.get_zoberist_hash(), BitBoard::start_position().get_zoberist_hash());
+}
+
+#[test]
+fn insufficient_material() {
+    let cases = [
+        ("8/8/8/3k4/8/8/8/4K3 w - - 0 1", true),
+        ("8/8/8/3k4/8/8/8/4KN2 w - - 0 1", true),
+        ("8/8/8/3k4/8/8/8/4KB2 w - - 0 1", true),
+        ("8/8/8/3bk3/8/8/8/4KB2 w - - 0 1", true),
+        ("8/8/8/3kb3/8/8/8/4KB2 w - - 0 1", false),
+        ("8/8/8/3k4/8/8/8/3NKN2 w - - 0 1", false),
+        ("8/8/8/3k4/8/8/8/4KR2 w - - 0 1", false),
+        ("8/8/8/3k4/8/8/4P3/4K3 w - - 0 1", false),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", false),
+    ];
+
+    for (fen, expected) in cases {
+        assert_eq!(BitBoard::from_fen(fen).insufficient_material(), expected, "{}", fen);
+    }
+
+    assert!(Game::from_fen("8/8/8/3k4/8/8/8/4KN2 w - - 0 1").get_game_state() == GameState::InsuffMaterial);
+}
+
+#[test]
+fn in_check() {
+    let cases = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", false),
+        ("4k3/8/8/8/8/8/8/4K2r w - - 0 1", true),
+        ("4k3/8/8/8/8/3n4/8/4K3 w - - 0 1", true),
+        ("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", true),
+        ("4k3/8/8/8/1b6/8/3P4/4K3 w - - 0 1", false),
+        ("4k3/8/8/8/8/8/8/4K2R b - - 0 1", false),
+        ("4k3/8/8/1B6/8/8/8/4K3 b - - 0 1", true),
+    ];
+
+    for (fen, expected) in cases {
+        assert_eq!(BitBoard::from_fen(fen).in_check(), expected, "{}", fen);
+    }
+}
+
+#[test]
+fn castling_rights() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+
+    let mut board = BitBoard::from_fen(fen);
+    play(&mut board, &["e1e2"]);
+    assert_eq!(board.get_fen(), "r3k2r/8/8/8/8/8/4K3/R6R b kq -");
+
+    let mut board = BitBoard::from_fen(fen);
+    play(&mut board, &["h1h2", "a8a7"]);
+    assert_eq!(board.get_fen(), "4k2r/r7/8/8/8/8/7R/R3K3 w Qk -");
+
+    //Capturing a rook on its start square removes the right of the other side
+    let mut board = BitBoard::from_fen(fen);
+    play(&mut board, &["a1a8"]);
+    assert_eq!(board.get_fen(), "R3k2r/8/8/8/8/8/8/4K2R b Kk -");
+
+    let mut board = BitBoard::from_fen(fen);
+    play(&mut board, &["e1g1", "e8c8"]);
+    assert_eq!(board.get_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - -");
+}
+
+#[test]
+fn castling_legality() {
+    //Through an attacked square
+    let board = BitBoard::from_fen("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1");
+    assert!(find_move(&board, "e1g1").is_none());
+    assert!(find_move(&board, "e1c1").is_some());
+
+    //Out of check
+    let board = BitBoard::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    assert!(find_move(&board, "e1g1").is_none());
+    assert!(find_move(&board, "e1c1").is_none());
+
+    //Only the king path has to be safe, b1 may be attacked on the long side
+    let board = BitBoard::from_fen("4k3/8/8/8/8/8/1r6/R3K2R w KQ - 0 1");
+    assert!(find_move(&board, "e1c1").is_some());
+
+    //Blocked
+    let board = BitBoard::from_fen("4k3/8/8/8/8/8/8/RN2K1NR w KQ - 0 1");
+    assert!(find_move(&board, "e1g1").is_none());
+    assert!(find_move(&board, "e1c1").is_none());
+}
+
+#[test]
+fn en_passant() {
+    //Set after a double push next to an enemy pawn and gone after the next move
+    let mut board = BitBoard::from_fen("4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1");
+    play(&mut board, &["e2e4"]);
+    assert_eq!(board.get_fen(), "4k3/8/8/8/4Pp2/8/8/4K3 b - e3");
+    assert!(find_move(&board, "f4e3").is_some());
+
+    play(&mut board, &["e8d8"]);
+    assert_eq!(board.get_fen(), "3k4/8/8/8/4Pp2/8/8/4K3 w - -");
+
+    //Capturing removes the pushed pawn
+    let mut board = BitBoard::from_fen("4k3/8/8/8/4Pp2/8/8/4K3 b - e3 0 1");
+    play(&mut board, &["f4e3"]);
+    assert_eq!(board.get_fen(), "4k3/8/8/8/8/4p3/8/4K3 w - -");
+
+    //Resolves a check given by the pushed pawn
+    let board = BitBoard::from_fen("8/8/8/4k3/3Pp3/8/8/4K3 b - d3 0 1");
+    assert!(board.in_check());
+    assert!(find_move(&board, "e4d3").is_some());
+}
+
+#[test]
+fn en_passant_pins() {
+    //Both pawns leave the rank, which opens it for the queen
+    let board = BitBoard::from_fen("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1");
+    assert!(find_move(&board, "e4d3").is_none());
+    assert!(find_move(&board, "e4e3").is_some());
+
+    //Diagonally pinned pawn leaving the pin line
+    let board = BitBoard::from_fen("8/8/8/3k4/3Pp3/8/6B1/4K3 b - d3 0 1");
+    assert!(find_move(&board, "e4d3").is_none());
+    assert!(find_move(&board, "e4e3").is_none());
+
+    //Diagonally pinned pawn capturing along the pin line
+    let board = BitBoard::from_fen("8/8/6k1/8/3Pp3/8/8/1B2K3 b - d3 0 1");
+    assert!(find_move(&board, "e4d3").is_some());
+    assert!(find_move(&board, "e4e3").is_none());
+}
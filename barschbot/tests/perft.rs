@@ -0,0 +1,97 @@
+use barschbot::{bit_board::BitBoard, game::Game, perft::{self, PerftTable}};
+
+//Deepest depth per standard position that still runs quickly in a debug build
+const TEST_DEPTHS: [u8; 6] = [4, 3, 5, 4, 3, 3];
+
+#[test]
+fn standard_positions() {
+    for (position, max_depth) in perft::get_standard_positions().iter().zip(TEST_DEPTHS) {
+        let board = position.get_board();
+
+        for (depth, expected) in position.expected.iter().filter(|(d, _)| *d <= max_depth) {
+            assert_eq!(perft::perft(&board, *depth), *expected, "{} at depth {}", position.fen, depth);
+        }
+    }
+}
+
+#[test]
+fn game_matches_board() {
+    for (position, max_depth) in perft::get_standard_positions().iter().zip(TEST_DEPTHS) {
+        let depth = max_depth.min(3);
+        let mut game = Game::from_fen(&position.fen);
+
+        assert_eq!(perft::perft_game(&mut game, depth), perft::perft(&position.get_board(), depth), "{}", position.fen);
+        assert_eq!(game.get_fen(), Game::from_fen(&position.fen).get_fen());
+    }
+}
+
+#[test]
+fn hashed_and_parallel() {
+    let table = PerftTable::new(4);
+
+    for position in perft::get_standard_positions() {
+        let board = position.get_board();
+        let (depth, expected) = position.expected[2];
+
+        assert_eq!(perft::perft_parallel(&board, depth, 4, Some(&table)), expected, "{}", position.fen);
+        //Second run is answered mostly from the table
+        assert_eq!(perft::perft_parallel(&board, depth, 1, Some(&table)), expected, "{}", position.fen);
+    }
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let board = BitBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let list = perft::divide(&board, 3, 1, None);
+
+    assert_eq!(list.len(), 48);
+    assert_eq!(list.iter().map(|(_, nodes)| nodes).sum::<u64>(), 97862);
+}
+
+#[test]
+fn epd_parsing() {
+    let position = perft::parse_epd_line("8/8/8/8/8/8/8/K6k w - - 0 1 ;D2 9 ;D1 3").unwrap();
+    assert_eq!(position.expected, vec![(1, 3), (2, 9)]);
+
+    assert!(perft::parse_epd_line("8/8/8 w - - 0 1 ;D1 3").is_err());
+    assert!(perft::parse_epd_line("8/8/8/8/8/8/8/K6k w - - 0 1 ;D1").is_err());
+    assert!(perft::parse_epd_line("8/8/8/8/8/8/8/K6k w - - 0 1 ;X1 3").is_err());
+}
+
+#[test]
+fn pseudo_legal_matches_legal() {
+    for (position, max_depth) in perft::get_standard_positions().iter().zip(TEST_DEPTHS) {
+        let board = position.get_board();
+        let depth = max_depth.min(4);
+
+        assert_eq!(perft::perft_pseudo_legal(&board, depth), perft::perft(&board, depth), "{}", position.fen);
+    }
+
+    let ep_fens = [
+        "8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1",
+        "8/8/8/3k4/3Pp3/8/6B1/4K3 b - d3 0 1",
+        "8/8/6k1/8/3Pp3/8/8/1B2K3 b - d3 0 1",
+        "8/8/8/4k3/3Pp3/8/8/4K3 b - d3 0 1",
+    ];
+
+    for fen in ep_fens {
+        let board = BitBoard::from_fen(fen);
+        assert_eq!(perft::perft_pseudo_legal(&board, 3), perft::perft(&board, 3), "{}", fen);
+    }
+}
+
+#[test]
+fn pseudo_legal_check() {
+    let boards = perft::get_standard_positions().iter().map(|p| p.get_board()).collect::<Vec<_>>();
+
+    //Moves of one position tried on every other one, like a colliding transposition table entry would
+    for from in &boards {
+        for to in &boards {
+            let generated = to.generate_pseudo_legal_moves();
+
+            for m in from.generate_pseudo_legal_moves() {
+                assert_eq!(to.is_pseudo_legal(m), generated.contains(&m), "{} in {}", m.get_uci(), to.get_fen());
+            }
+        }
+    }
+}
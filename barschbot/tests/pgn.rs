@@ -0,0 +1,130 @@
+use barschbot::{chess_move::ChessMove, game::Game, game_result::{GameResult, ResultReason}, karpfen_bot::CHECKMATE_VALUE, pgn::{self, PgnError, PgnGame}};
+
+fn play(fen: &str, moves: &[&str]) -> Game {
+    let mut game = Game::from_fen(fen);
+
+    for san in moves {
+        let m = ChessMove::from_san(san, &game.get_board()).unwrap();
+        game.make_move(m);
+    }
+
+    return game;
+}
+
+fn get_ucis(game: &Game) -> Vec<String> {
+    return game.get_moves().iter().map(|m| m.get_uci()).collect();
+}
+
+#[test]
+fn round_trip() {
+    let moves = ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O", "Nf6", "d4", "exd4", "e5", "d5", "exf6", "dxc4", "Re1+", "Be6"];
+    let game = play("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &moves);
+
+    let mut pgn = PgnGame::new(game);
+    pgn.set_tag("White", "BarschBot");
+    pgn.start_comment = "Two knights".to_owned();
+    pgn.comments[2] = "+0.35/12".to_owned();
+    pgn.comments[14] = "check".to_owned();
+
+    let text = pgn.to_pgn();
+    let parsed = pgn::parse_pgn(&text).unwrap();
+
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(get_ucis(&parsed[0].game), get_ucis(&pgn.game));
+    assert_eq!(parsed[0].game.get_fen(), pgn.game.get_fen());
+    assert_eq!(parsed[0].start_comment, "Two knights");
+    assert_eq!(parsed[0].comments, pgn.comments);
+    assert_eq!(parsed[0].get_tag("White"), Some("BarschBot"));
+    assert_eq!(parsed[0].to_pgn(), text);
+
+    //Custom start with black to move and a promotion
+    let fen = "8/8/8/8/8/4k3/p7/2K5 b - - 0 40";
+    let mut pgn = PgnGame::new(play(fen, &["a1=Q+", "Kc2", "Qa2+"]));
+    pgn.comments[1] = "forced".to_owned();
+
+    let text = pgn.to_pgn();
+    assert!(text.contains("[SetUp \"1\"]"));
+    assert!(text.contains("40... a1=Q+ 41. Kc2 {forced} 41... Qa2+"));
+
+    let parsed = pgn::parse_pgn(&text).unwrap();
+    assert_eq!(parsed[0].game.get_start_fen(), fen);
+    assert_eq!(get_ucis(&parsed[0].game), vec!["a2a1q", "c1c2", "a1a2"]);
+    assert_eq!(parsed[0].to_pgn(), text);
+}
+
+#[test]
+fn multiple_games() {
+    let mut mate = PgnGame::from_game(play("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["f3", "e5", "g4", "Qh4#"]));
+    mate.set_tag("Round", "1");
+    let mut draw = PgnGame::new(play("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &["d4", "d5"]));
+    draw.set_tag("Round", "2");
+    draw.set_result(&GameResult::draw(ResultReason::Adjudication));
+
+    let parsed = pgn::parse_pgn(&pgn::write_pgn(&[mate, draw])).unwrap();
+
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].get_result(), "0-1");
+    assert_eq!(parsed[0].get_tag("Termination"), Some("normal"));
+    assert_eq!(parsed[0].get_tag("Round"), Some("1"));
+    assert_eq!(get_ucis(&parsed[0].game), vec!["f2f3", "e7e5", "g2g4", "d8h4"]);
+    assert_eq!(parsed[1].get_result(), "1/2-1/2");
+    assert_eq!(parsed[1].get_tag("Round"), Some("2"));
+    assert_eq!(get_ucis(&parsed[1].game), vec!["d2d4", "d7d5"]);
+
+    //Games without tags, variations, nags and ; comments
+    let text = "1. e4 (1. d4 d5) e5 $1 2. Nf3 ; rest of the line\n*\n\n[Event \"b\"]\n\n1.d4 {main} 1... Nf6 1-0\n";
+    let parsed = pgn::parse_pgn(text).unwrap();
+
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(get_ucis(&parsed[0].game), vec!["e2e4", "e7e5", "g1f3"]);
+    assert_eq!(parsed[0].get_result(), "*");
+    assert_eq!(parsed[1].get_tag("Event"), Some("b"));
+    assert_eq!(parsed[1].comments, vec!["main", ""]);
+    assert_eq!(parsed[1].get_result(), "1-0");
+}
+
+#[test]
+fn tags() {
+    let pgn = PgnGame::new(Game::get_start_position());
+    let text = pgn.to_pgn();
+    assert!(text.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"*\"]\n\n*\n"));
+
+    let mut pgn = PgnGame::new(Game::get_start_position());
+    pgn.set_tag("Event", "The \"Barsch\" \\ Karpfen cup");
+    pgn.set_tag("TimeControl", "40/60");
+
+    let parsed = pgn::parse_pgn(&pgn.to_pgn()).unwrap();
+    assert_eq!(parsed[0].get_tag("Event"), Some("The \"Barsch\" \\ Karpfen cup"));
+    assert_eq!(parsed[0].get_tag("TimeControl"), Some("40/60"));
+    assert_eq!(parsed[0].get_tag("Annotator"), None);
+
+    assert!(matches!(pgn::parse_pgn("[Event \"a\"\n\n*"), Err(PgnError::InvalidTag(_))));
+    assert!(matches!(pgn::parse_pgn("[Event a]\n\n*"), Err(PgnError::InvalidTag(_))));
+    assert!(matches!(pgn::parse_pgn("[FEN \"8/8/8 w - - 0 1\"]\n\n*"), Err(PgnError::InvalidFen(_))));
+}
+
+#[test]
+fn eval_comments() {
+    //A middlegame pawn is a thousand units
+    assert_eq!(pgn::get_eval_comment(1500, 7), "+1.50/7");
+    assert_eq!(pgn::get_eval_comment(-255, 3), "-0.25/3");
+    assert_eq!(pgn::get_eval_comment(0, 1), "+0.00/1");
+    assert_eq!(pgn::get_eval_comment(CHECKMATE_VALUE - 3, 5), "+M2/5");
+    assert_eq!(pgn::get_eval_comment(-CHECKMATE_VALUE + 2, 4), "-M1/4");
+}
+
+#[test]
+fn null_moves_and_errors() {
+    let parsed = pgn::parse_pgn("{Before the first move} 1. e4 -- 2. d4 *").unwrap();
+    assert_eq!(parsed[0].start_comment, "Before the first move");
+    assert_eq!(get_ucis(&parsed[0].game).len(), 3);
+    assert!(parsed[0].game.get_moves()[1].is_null_move());
+    assert!(parsed[0].to_pgn().contains("{Before the first move} 1. e4 -- 2. d4 *"));
+
+    //In check and after mate
+    assert_eq!(pgn::parse_pgn("1. e4 f5 2. Qh5+ -- *").err(), Some(PgnError::IllegalNullMove(0)));
+    assert_eq!(pgn::parse_pgn("[Event \"a\"]\n\n1. e4 e5 *\n\n[Event \"b\"]\n\n1. f3 e5 2. g4 Qh4# -- 0-1").err(), Some(PgnError::IllegalNullMove(1)));
+
+    assert!(matches!(pgn::parse_pgn("1. e4 e5 2. Ke3 *"), Err(PgnError::IllegalMove(0, _, _))));
+    assert_eq!(pgn::parse_pgn("1. e4 {open *").err(), Some(PgnError::UnterminatedComment));
+}
@@ -0,0 +1,117 @@
+use barschbot::{bit_board::BitBoard, perft, san::{self, SanError}};
+
+fn get_san(fen: &str, uci: &str) -> String {
+    let board = BitBoard::from_fen(fen);
+    let m = board.get_legal_moves().into_iter().find(|m| m.get_uci() == uci).unwrap();
+
+    return san::get_san(m, &board);
+}
+
+fn parse_uci(fen: &str, san: &str) -> String {
+    return san::parse_san(san, &BitBoard::from_fen(fen)).unwrap().get_uci();
+}
+
+#[test]
+fn disambiguation() {
+    //Knights on b1 and f1 both reach d2
+    let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(get_san(fen, "b1d2"), "Nbd2");
+    assert_eq!(get_san(fen, "f1d2"), "Nfd2");
+    assert_eq!(parse_uci(fen, "Nbd2"), "b1d2");
+    assert_eq!(parse_uci(fen, "Nfd2"), "f1d2");
+    assert!(matches!(san::parse_san("Nd2", &BitBoard::from_fen(fen)), Err(SanError::Ambiguous(_))));
+
+    //Rooks on the same file
+    let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(get_san(fen, "a1a3"), "R1a3");
+    assert_eq!(get_san(fen, "a5a3"), "R5a3");
+    assert_eq!(parse_uci(fen, "R1a3"), "a1a3");
+    assert_eq!(parse_uci(fen, "R5a3"), "a5a3");
+
+    //Three queens reach e4, the one on h1 shares a file with h4 and a rank with e1
+    let fen = "K7/8/k7/8/7Q/8/8/4Q2Q w - - 0 1";
+    assert_eq!(get_san(fen, "h1e4"), "Qh1e4");
+    assert_eq!(get_san(fen, "h4e4"), "Q4e4");
+    assert_eq!(get_san(fen, "e1e4"), "Qee4");
+    assert_eq!(parse_uci(fen, "Qh1e4"), "h1e4");
+    assert_eq!(parse_uci(fen, "Qh1xe4"), "h1e4");
+    assert_eq!(parse_uci(fen, "Q4e4"), "h4e4");
+}
+
+#[test]
+fn castling() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(get_san(fen, "e1g1"), "O-O");
+    assert_eq!(get_san(fen, "e1c1"), "O-O-O");
+
+    for (san, uci) in [("O-O", "e1g1"), ("0-0", "e1g1"), ("O-O-O", "e1c1"), ("0-0-0", "e1c1")] {
+        assert_eq!(parse_uci(fen, san), uci, "{}", san);
+    }
+
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1";
+    assert_eq!(parse_uci(fen, "0-0"), "e8g8");
+    assert_eq!(parse_uci(fen, "O-O-O"), "e8c8");
+
+    let board = BitBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1");
+    assert!(matches!(san::parse_san("O-O", &board), Err(SanError::NoMatchingMove(_))));
+}
+
+#[test]
+fn promotions_and_suffixes() {
+    let fen = "8/P6k/8/8/8/8/8/K7 w - - 0 1";
+    assert_eq!(get_san(fen, "a7a8q"), "a8=Q");
+    assert_eq!(get_san(fen, "a7a8n"), "a8=N");
+    assert_eq!(parse_uci(fen, "a8=Q"), "a7a8q");
+    assert_eq!(parse_uci(fen, "a8Q"), "a7a8q");
+    assert_eq!(parse_uci(fen, "a8=R"), "a7a8r");
+    assert_eq!(parse_uci(fen, "a8N"), "a7a8n");
+    assert!(matches!(san::parse_san("a8", &BitBoard::from_fen(fen)), Err(SanError::NoMatchingMove(_))));
+
+    //Promotion with check
+    let fen = "7k/P7/8/8/8/8/8/K7 w - - 0 1";
+    assert_eq!(get_san(fen, "a7a8q"), "a8=Q+");
+    assert_eq!(parse_uci(fen, "a8=Q+"), "a7a8q");
+
+    let fen = "6k1/5ppp/8/8/8/8/8/3R2K1 w - - 0 1";
+    assert_eq!(get_san(fen, "d1d8"), "Rd8#");
+    assert_eq!(get_san(fen, "d1d7"), "Rd7");
+    for san in ["Rd8#", "Rd8+", "Rd8", "Rd8#!"] {
+        assert_eq!(parse_uci(fen, san), "d1d8", "{}", san);
+    }
+
+    let fen = "4k3/8/8/8/8/8/8/3RK3 w - - 0 1";
+    assert_eq!(get_san(fen, "d1d8"), "Rd8+");
+}
+
+#[test]
+fn invalid_san() {
+    let board = BitBoard::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+    for san in ["", "Zf3", "Nf9", "e", "Ke2e3e4"] {
+        assert!(matches!(san::parse_san(san, &board), Err(SanError::InvalidSyntax(_))), "{}", san);
+    }
+
+    assert!(matches!(san::parse_san("e5", &board), Err(SanError::NoMatchingMove(_))));
+    assert!(matches!(san::parse_san("Nc3=Q", &board), Err(SanError::InvalidSyntax(_))));
+}
+
+#[test]
+fn round_trip() {
+    for position in perft::get_standard_positions() {
+        let board = position.get_board();
+
+        for m in board.get_legal_moves() {
+            let san = san::get_san(m, &board);
+            assert!(san::parse_san(&san, &board) == Ok(m), "{} in {}", san, position.fen);
+
+            //And one ply deeper to reach more pieces and checks
+            let mut after = board.clone();
+            after.make_move(m);
+
+            for reply in after.get_legal_moves() {
+                let san = san::get_san(reply, &after);
+                assert!(san::parse_san(&san, &after) == Ok(reply), "{} after {} in {}", san, m.get_uci(), position.fen);
+            }
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use std::{thread, time::{Duration, Instant}};
+
+use barschbot::{barsch_bot, bb_settings, chess_move::{self, ChessMove}, endgame_table::EndgameTable, game::Game, karpfen_bot::{KarpfenBot, CHECKMATE_VALUE}, kb_settings::{self, KBSettings}, opening_book::OpeningBook, search_info::{self, InfoLog, InfoScore, SearchInfo, SilentReporter}, time_manager::SearchLimits, uci};
+
+fn get_settings(depth: u8, thread_count: usize) -> KBSettings {
+    let mut settings = kb_settings::STANDARD_KB_SETTINGS;
+    settings.max_depth = depth;
+    settings.min_search_time = 0;
+    settings.thread_count = thread_count;
+
+    return settings;
+}
+
+fn search(bot: &mut KarpfenBot, fen: &str) -> (ChessMove, i32) {
+    let mut game = Game::from_fen(fen);
+    let m = bot.get_best_move(&mut game, &OpeningBook::new(), &EndgameTable::empty());
+
+    return (m, bot.get_last_score());
+}
+
+#[test]
+fn single_thread_is_deterministic() {
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ];
+
+    for fen in fens {
+        let (m1, s1) = search(&mut KarpfenBot::with_settings(get_settings(4, 1)), fen);
+        let (m2, s2) = search(&mut KarpfenBot::with_settings(get_settings(4, 1)), fen);
+
+        assert!(m1 == m2, "{}", fen);
+        assert_eq!(s1, s2, "{}", fen);
+    }
+}
+
+#[test]
+fn threads_find_mate() {
+    let mut bot = KarpfenBot::with_settings(get_settings(4, 4));
+
+    let (m, score) = search(&mut bot, "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1");
+    assert_eq!(m.get_uci(), "d1d8");
+    assert_eq!(score, CHECKMATE_VALUE - 1);
+    assert!(bot.get_stats().nodes > 0);
+
+    //The helpers are reused for the next move
+    let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    let (m, _) = search(&mut bot, fen);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+
+    //Going back to a single thread must not inherit the stop of the threaded search
+    bot.set_settings(get_settings(4, 1));
+    let (m, _) = search(&mut bot, fen);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+}
+
+#[test]
+fn search_limits() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    let mut bot = KarpfenBot::with_settings(get_settings(64, 1));
+
+    let mut limits = SearchLimits::new();
+    limits.movetime = Some(200);
+
+    let start = Instant::now();
+    let m = bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter);
+    assert!(start.elapsed().as_millis() < 1000);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+    assert!(bot.get_last_depth() >= 1);
+
+    let mut limits = SearchLimits::new();
+    limits.nodes = Some(10_000);
+
+    bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter);
+    assert!(bot.get_stats().nodes <= 10_000);
+
+    //Aborted in the first iteration, there still has to be a move
+    limits.nodes = Some(10);
+    let m = bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+
+    let mut limits = SearchLimits::new();
+    limits.depth = Some(3);
+    bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter);
+    assert_eq!(bot.get_last_depth(), 3);
+}
+
+#[test]
+fn barsch_bot_movetime() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    let mut settings = bb_settings::STANDARD_BB_SETTINGS;
+    settings.max_depth = 64;
+
+    let mut limits = SearchLimits::new();
+    limits.movetime = Some(200);
+
+    let start = Instant::now();
+    let m = barsch_bot::get_best_move_with_limits(&mut Game::from_fen(fen), &EndgameTable::empty(), &settings, &OpeningBook::new(), &limits, &mut SilentReporter);
+    assert!(start.elapsed().as_millis() < 1000);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+}
+
+fn assert_legal_line(fen: &str, pv: &[ChessMove]) {
+    let mut game = Game::from_fen(fen);
+
+    for m in pv {
+        assert!(game.get_legal_moves().contains(m), "{} in {}", m.get_uci(), fen);
+        game.make_move(*m);
+    }
+}
+
+#[test]
+fn info_output() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let mut bot = KarpfenBot::with_settings(get_settings(5, 1));
+    let mut log = InfoLog::new();
+
+    let m = bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &SearchLimits::from_depth(5, 0), &mut log);
+
+    assert_eq!(log.infos.iter().map(|info| info.depth).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+
+    let last = log.get_last().unwrap();
+    assert!(last.pv[0] == m);
+    assert!(last.pv == bot.get_pv());
+    assert!(last.pv.len() > 1);
+    assert!(last.seldepth >= 5);
+    assert_eq!(last.score, InfoScore::Centipawns(search_info::to_centipawns(bot.get_last_score())));
+    assert_legal_line(fen, &last.pv);
+
+    let line = last.to_uci();
+    assert!(line.starts_with("depth 5 seldepth "));
+    assert!(line.contains(&format!(" pv {}", m.get_uci())));
+
+    let mut log = InfoLog::new();
+    bot.get_best_move_with_limits(&mut Game::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1"), &OpeningBook::new(), &EndgameTable::empty(), &SearchLimits::from_depth(3, 0), &mut log);
+    assert_eq!(log.get_last().unwrap().score, InfoScore::Mate(1));
+    assert_eq!(log.get_last().unwrap().score.to_uci(), "mate 1");
+
+    assert_eq!(InfoScore::from_score(-CHECKMATE_VALUE + 4), InfoScore::Mate(-2));
+    //A pawn is a thousand units
+    assert_eq!(InfoScore::from_score(1000), InfoScore::Centipawns(100));
+    assert_eq!(InfoScore::from_score(-255), InfoScore::Centipawns(-25));
+
+    //The pv ends at the first null move
+    let mut game = Game::get_start_position();
+    let e4 = game.get_uci_move("e2e4".to_owned());
+    let mut info = SearchInfo { depth: 1, seldepth: 1, multipv: 1, score: InfoScore::Centipawns(0), nodes: 1, time: 1, hashfull: None, pv: vec![e4, chess_move::NULL_MOVE, e4] };
+    assert!(info.to_uci().ends_with(" pv e2e4"));
+
+    info.pv = vec![chess_move::NULL_MOVE];
+    assert!(!info.to_uci().contains(" pv"));
+}
+
+#[test]
+fn barsch_bot_info_output() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    let mut settings = bb_settings::STANDARD_BB_SETTINGS;
+    settings.max_depth = 3;
+    let mut log = InfoLog::new();
+
+    let m = barsch_bot::get_best_move_with_limits(&mut Game::from_fen(fen), &EndgameTable::empty(), &settings, &OpeningBook::new(), &SearchLimits::from_depth(3, 0), &mut log);
+
+    assert_eq!(log.infos.len(), 3);
+    let last = log.get_last().unwrap();
+    assert!(last.pv[0] == m);
+    assert_legal_line(fen, &last.pv);
+
+    let mut log = InfoLog::new();
+    barsch_bot::get_best_move_with_limits(&mut Game::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1"), &EndgameTable::empty(), &settings, &OpeningBook::new(), &SearchLimits::from_depth(3, 0), &mut log);
+    assert_eq!(log.get_last().unwrap().score, InfoScore::Mate(1));
+
+    //Mate in 2 that the extension of the forced recapture finds at depth 1
+    let mut log = InfoLog::new();
+    let m = barsch_bot::get_best_move_with_limits(&mut Game::from_fen("1r4k1/5ppp/8/8/8/8/4RPPP/4R1K1 w - - 0 1"), &EndgameTable::empty(), &settings, &OpeningBook::new(), &SearchLimits::from_depth(3, 0), &mut log);
+    assert_eq!(m.get_uci(), "e2e8");
+    assert!(log.get_last().unwrap().score != InfoScore::Mate(1));
+}
+
+#[test]
+fn multi_pv() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    let mut bot = KarpfenBot::with_settings(get_settings(4, 1));
+    let mut log = InfoLog::new();
+
+    let lines = bot.analyse(&mut Game::from_fen(fen), &EndgameTable::empty(), &SearchLimits::from_depth(4, 0), 3, &mut log);
+
+    assert_eq!(lines.len(), 3);
+    for i in 0..lines.len() {
+        assert!(lines[i].pv[0] == lines[i].root_move);
+        assert_legal_line(fen, &lines[i].pv);
+
+        for j in (i + 1)..lines.len() {
+            assert!(lines[i].root_move != lines[j].root_move);
+            assert!(lines[i].score >= lines[j].score);
+        }
+    }
+
+    //Every depth reports every line
+    assert_eq!(log.infos.len(), 4 * 3);
+    assert_eq!(log.infos.iter().rev().take(3).map(|info| info.multipv).collect::<Vec<_>>(), vec![3, 2, 1]);
+    assert!(bot.get_last_score() == lines[0].score);
+
+    //Mate first, the other lines cannot be better than the back rank mate
+    let fen = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1";
+    let lines = bot.analyse(&mut Game::from_fen(fen), &EndgameTable::empty(), &SearchLimits::from_depth(3, 0), 100, &mut SilentReporter);
+    assert_eq!(lines.len(), Game::from_fen(fen).get_legal_moves().len());
+    assert_eq!(lines[0].root_move.get_uci(), "d1d8");
+    assert_eq!(lines[0].score, CHECKMATE_VALUE - 1);
+    assert!(lines[1].score < CHECKMATE_VALUE - 1000);
+
+    //analyse does not change the settings
+    assert_eq!(bot.get_settings().multi_pv, 1);
+}
+
+#[test]
+fn ponder() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    let mut bot = KarpfenBot::with_settings(get_settings(64, 1));
+    let handle = bot.get_search_handle();
+
+    let mut limits = SearchLimits::new();
+    limits.movetime = Some(100);
+    limits.ponder = true;
+
+    //The movetime only counts after ponderhit
+    handle.reset(true);
+    let (m, ponder_move, elapsed) = thread::scope(|scope| {
+        let search = scope.spawn(|| {
+            let mut game = Game::from_fen(fen);
+            let m = bot.get_best_move_with_limits(&mut game, &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter);
+            return (m, bot.get_ponder_move(&mut game, m));
+        });
+
+        thread::sleep(Duration::from_millis(300));
+        assert!(!search.is_finished());
+
+        let start = Instant::now();
+        handle.ponderhit();
+        let (m, ponder_move) = search.join().unwrap();
+
+        return (m, ponder_move, start.elapsed().as_millis());
+    });
+
+    assert!(elapsed < 1000, "{}", elapsed);
+
+    let mut game = Game::from_fen(fen);
+    assert!(game.get_legal_moves().contains(&m));
+    game.make_move(m);
+    assert!(game.get_legal_moves().contains(&ponder_move));
+
+    //The ponder move was not played, stop ends the search and the next one starts from a clean handle
+    limits.movetime = None;
+    handle.reset(true);
+    thread::scope(|scope| {
+        let search = scope.spawn(|| {
+            bot.get_best_move_with_limits(&mut Game::from_fen(fen), &OpeningBook::new(), &EndgameTable::empty(), &limits, &mut SilentReporter)
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        handle.stop();
+        search.join().unwrap();
+    });
+
+    handle.reset(false);
+    bot.set_settings(get_settings(4, 1));
+    let (m, _) = search(&mut bot, "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1");
+    assert_eq!(m.get_uci(), "d1d8");
+}
+
+//Mates in 3 where only the mating move gives check, so every move before it can be reduced or pruned
+const QUIET_MATES: [&str; 2] = ["k7/8/8/3K4/8/8/8/7R w - - 0 1", "7k/8/8/4K3/8/8/8/1Q6 w - - 0 1"];
+
+fn get_unpruned_settings(depth: u8) -> KBSettings {
+    let mut settings = get_settings(depth, 1);
+    settings.null_move_pruning = false;
+    settings.reverse_futility_pruning = false;
+    settings.razoring = false;
+    settings.futility_pruning = false;
+    settings.late_move_pruning = false;
+
+    return settings;
+}
+
+fn search_mate(settings: KBSettings, fen: &str, plies: i32) -> KarpfenBot {
+    let mut bot = KarpfenBot::with_settings(settings);
+    let (m, score) = search(&mut bot, fen);
+
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+    assert_eq!(score, CHECKMATE_VALUE - plies, "{}", fen);
+
+    return bot;
+}
+
+#[test]
+fn pvs_and_lmr() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    let mut plain_settings = get_settings(5, 1);
+    plain_settings.principal_variation_search = false;
+    plain_settings.late_move_reductions = false;
+
+    let mut plain = KarpfenBot::with_settings(plain_settings);
+    let mut reduced = KarpfenBot::with_settings(get_settings(5, 1));
+
+    let (m, _) = search(&mut reduced, fen);
+    search(&mut plain, fen);
+
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+    assert!(reduced.get_stats().late_move_reductions > 0);
+    assert!(reduced.get_stats().nodes < plain.get_stats().nodes);
+    assert_eq!(plain.get_stats().late_move_reductions, 0);
+    assert_eq!(plain.get_stats().re_searches, 0);
+
+    for fen in QUIET_MATES {
+        //Null windows alone keep the full depth, the mate has to show up as soon as it fits
+        let mut settings = get_unpruned_settings(5);
+        settings.late_move_reductions = false;
+        let bot = search_mate(settings, fen, 5);
+        assert!(bot.get_stats().re_searches > 0);
+
+        //Reductions may push the mate to a later iteration, but must not make it longer
+        let bot = search_mate(get_unpruned_settings(8), fen, 5);
+        assert!(bot.get_stats().late_move_reductions > 0);
+    }
+}
+
+fn get_prune_count(bot: &KarpfenBot) -> u64 {
+    let stats = bot.get_stats();
+
+    return stats.null_move_prunes + stats.reverse_futility_prunes + stats.razor_prunes + stats.futility_prunes + stats.late_move_prunes;
+}
+
+#[test]
+fn forward_pruning() {
+    let fen = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    let mut pruned = KarpfenBot::with_settings(get_settings(6, 1));
+    let mut unpruned = KarpfenBot::with_settings(get_unpruned_settings(6));
+
+    let (m, _) = search(&mut pruned, fen);
+    search(&mut unpruned, fen);
+
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+    assert!(pruned.get_stats().nodes < unpruned.get_stats().nodes);
+    assert_eq!(get_prune_count(&unpruned), 0);
+
+    //Each technique on its own has to cut into the quiet mate and still keep its length
+    for i in 0..5 {
+        let mut settings = get_unpruned_settings(8);
+
+        match i {
+            0 => settings.null_move_pruning = true,
+            1 => settings.reverse_futility_pruning = true,
+            2 => settings.razoring = true,
+            3 => settings.futility_pruning = true,
+            _ => settings.late_move_pruning = true,
+        }
+
+        let bot = search_mate(settings, QUIET_MATES[0], 5);
+        assert!(get_prune_count(&bot) > 0, "{}", i);
+    }
+
+    for fen in QUIET_MATES {
+        search_mate(get_settings(8, 1), fen, 5);
+    }
+}
+
+#[test]
+fn null_move_verification() {
+    //Without the verification search the null move cutoffs pick e4e3 here and miss the white queen
+    let fen = "8/7p/8/1P6/3Kp2P/8/2k5/8 b - - 0 1";
+
+    let mut settings = get_settings(9, 1);
+    settings.null_move_pruning = false;
+
+    let mut plain = KarpfenBot::with_settings(settings);
+    let (plain_move, plain_score) = search(&mut plain, fen);
+
+    let mut verified = KarpfenBot::with_settings(get_settings(9, 1));
+    let (m, score) = search(&mut verified, fen);
+
+    assert!(verified.get_stats().null_move_refutations > 0);
+    assert!(m == plain_move);
+    assert_eq!(score, plain_score);
+    assert_eq!(m.get_uci(), "c2d2");
+}
+
+#[test]
+fn drawn_root_still_moves() {
+    let mut bot = KarpfenBot::with_settings(get_settings(4, 1));
+
+    //The fifty move rule is already reached, but the GUI still asks for a move
+    let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 100 80";
+    let (m, _) = search(&mut bot, fen);
+    assert!(Game::from_fen(fen).get_legal_moves().contains(&m));
+
+    //Threefold repetition through the move history
+    let mut game = uci::parse_position(&["startpos", "moves", "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"]).unwrap();
+    assert!(game.is_threefold_repetition());
+
+    let m = bot.get_best_move(&mut game, &OpeningBook::new(), &EndgameTable::empty());
+    assert!(game.get_legal_moves().contains(&m));
+    assert!(bot.get_pv().iter().all(|m| !m.is_null_move()));
+}
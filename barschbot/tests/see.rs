@@ -0,0 +1,52 @@
+use barschbot::{bit_board::BitBoard, chess_move::ChessMove};
+
+fn get_move(board: &BitBoard, uci: &str) -> ChessMove {
+    return board.get_legal_moves().into_iter().find(|m| m.get_uci() == uci).unwrap_or_else(|| panic!("{} is not legal", uci));
+}
+
+#[test]
+fn exchanges() {
+    let cases = [
+        //Undefended pawn
+        ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5", 100),
+        //Knight for a pawn, the queen behind the knight does not help
+        ("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5", -200),
+        //Quiet move onto a square covered by a pawn
+        ("4k3/8/8/4p3/8/8/8/4KN2 w - - 0 1", "f1d2", 0),
+        ("4k3/8/8/4p3/8/5N2/8/4K3 w - - 0 1", "f3d4", -300),
+        //En passant
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100),
+        //Promotion on a defended square
+        ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", -100),
+        ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", 1300),
+    ];
+
+    for (fen, uci, expected) in cases {
+        let board = BitBoard::from_fen(fen);
+        assert_eq!(board.see(get_move(&board, uci)), expected, "{} {}", fen, uci);
+    }
+}
+
+#[test]
+fn x_rays() {
+    //The second rook backs up the first one
+    let board = BitBoard::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1");
+    assert_eq!(board.see(get_move(&board, "d2d5")), 100);
+
+    let board = BitBoard::from_fen("3rk3/3r4/8/3p4/8/8/8/3RK3 w - - 0 1");
+    assert_eq!(board.see(get_move(&board, "d1d5")), -400);
+
+    //Bishop behind the capturing pawn
+    let board = BitBoard::from_fen("3qk3/8/8/3p4/4P3/5B2/8/4K3 w - - 0 1");
+    assert_eq!(board.see(get_move(&board, "e4d5")), 100);
+}
+
+#[test]
+fn thresholds() {
+    let board = BitBoard::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1");
+    let m = get_move(&board, "d3e5");
+
+    assert!(board.see_ge(m, -200));
+    assert!(!board.see_ge(m, -199));
+    assert!(!board.see_ge(m, 0));
+}
@@ -0,0 +1,78 @@
+use barschbot::{time_manager::{SearchLimits, TimeManager}, uci};
+
+#[test]
+fn parse_go() {
+    let limits = uci::parse_go(&["wtime", "60000", "btime", "50000", "winc", "1000", "binc", "500", "movestogo", "20"]);
+    assert_eq!(limits.wtime, Some(60000));
+    assert_eq!(limits.btime, Some(50000));
+    assert_eq!(limits.winc, 1000);
+    assert_eq!(limits.binc, 500);
+    assert_eq!(limits.movestogo, Some(20));
+    assert!(limits.is_limited());
+
+    let limits = uci::parse_go(&["depth", "7", "nodes", "5000", "mate", "3", "movetime", "100"]);
+    assert_eq!(limits.depth, Some(7));
+    assert_eq!(limits.nodes, Some(5000));
+    assert_eq!(limits.mate, Some(3));
+    assert_eq!(limits.movetime, Some(100));
+
+    assert!(uci::parse_go(&["infinite"]).infinite);
+    assert!(uci::parse_go(&["ponder", "wtime", "1000"]).ponder);
+    assert!(!uci::parse_go(&[]).is_limited());
+}
+
+#[test]
+fn depth_and_min_time() {
+    let manager = TimeManager::new(&SearchLimits::from_depth(5, 0), true);
+    assert!(manager.should_start_iteration(4, None));
+    assert!(!manager.should_start_iteration(5, None));
+
+    //The depth alone is not enough while the minimum time is not used up
+    let manager = TimeManager::new(&SearchLimits::from_depth(5, 60_000), true);
+    assert!(manager.should_start_iteration(5, None));
+}
+
+#[test]
+fn clock() {
+    let mut limits = SearchLimits::new();
+    limits.wtime = Some(0);
+    limits.btime = Some(3_600_000);
+
+    //White is out of time, black has plenty
+    let mut manager = TimeManager::new(&limits, true);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    assert!(!manager.should_start_iteration(1, None));
+    assert!(manager.check(1024));
+    assert!(manager.is_stopped());
+
+    let mut manager = TimeManager::new(&limits, false);
+    assert!(manager.should_start_iteration(30, None));
+    assert!(!manager.check(1024));
+}
+
+#[test]
+fn nodes_and_mate() {
+    let mut limits = SearchLimits::new();
+    limits.nodes = Some(100);
+    limits.mate = Some(2);
+
+    let mut manager = TimeManager::new(&limits, true);
+    assert!(!manager.check(99));
+    assert!(manager.check(100));
+
+    let manager = TimeManager::new(&limits, true);
+    assert!(manager.should_start_iteration(3, Some(5)));
+    assert!(!manager.should_start_iteration(3, Some(3)));
+}
+
+#[test]
+fn infinite_ignores_the_rest() {
+    let mut limits = SearchLimits::from_depth(1, 0);
+    limits.movetime = Some(0);
+    limits.infinite = true;
+
+    let mut manager = TimeManager::new(&limits, true);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    assert!(manager.should_start_iteration(60, None));
+    assert!(!manager.check(1024));
+}
@@ -0,0 +1,152 @@
+use barschbot::{bit_board::BitBoard, chess_move::{self, ChessMove}, karpfen_bot::CHECKMATE_VALUE, transposition_table::{NodeType, TranspositionTable}};
+
+fn get_move(board: &BitBoard, uci: &str) -> ChessMove {
+    return board.get_legal_moves().into_iter().find(|m| m.get_uci() == uci).unwrap_or_else(|| panic!("{} is not legal", uci));
+}
+
+#[test]
+fn store_and_probe() {
+    let table = TranspositionTable::new(1);
+    let board = BitBoard::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let m = get_move(&board, "e2a6");
+
+    assert!(table.probe(&board, 0).is_none());
+
+    table.store(&board, 3, 5, -123, m, NodeType::LowerBound);
+    let entry = table.probe(&board, 3).unwrap();
+
+    assert_eq!(entry.depth, 5);
+    assert_eq!(entry.score, -123);
+    assert!(entry.best_move == m);
+    assert_eq!(entry.node_type, NodeType::LowerBound);
+
+    let mut other = board.clone();
+    other.make_move(m);
+    assert!(table.probe(&other, 4).is_none());
+}
+
+#[test]
+fn moves_keep_their_pieces() {
+    let table = TranspositionTable::new(1);
+
+    for (fen, uci) in [
+        ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"),
+        ("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"),
+        ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"),
+        ("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", "e1c1"),
+    ] {
+        let board = BitBoard::from_fen(fen);
+        let m = get_move(&board, uci);
+
+        table.store(&board, 0, 1, 0, m, NodeType::Exact);
+        assert!(table.probe(&board, 0).unwrap().best_move == m, "{}", uci);
+    }
+}
+
+#[test]
+fn mate_scores_are_relative_to_the_node() {
+    let table = TranspositionTable::new(1);
+    let board = BitBoard::start_position();
+
+    //Mate in 3 plies from a node at ply 4 is mate in 5 plies from a node at ply 2
+    table.store(&board, 4, 3, CHECKMATE_VALUE - 7, chess_move::NULL_MOVE, NodeType::Exact);
+    assert_eq!(table.probe(&board, 2).unwrap().score, CHECKMATE_VALUE - 5);
+
+    table.store(&board, 4, 4, -CHECKMATE_VALUE + 7, chess_move::NULL_MOVE, NodeType::Exact);
+    assert_eq!(table.probe(&board, 2).unwrap().score, -CHECKMATE_VALUE + 5);
+}
+
+#[test]
+fn replacement() {
+    let table = TranspositionTable::new(1);
+    let board = BitBoard::start_position();
+    let m = get_move(&board, "e2e4");
+
+    table.store(&board, 0, 6, 50, m, NodeType::Exact);
+
+    //Shallower results do not replace deeper ones, but keep the move when they have none
+    table.store(&board, 0, 2, 10, chess_move::NULL_MOVE, NodeType::Exact);
+    assert_eq!(table.probe(&board, 0).unwrap().depth, 6);
+
+    table.store(&board, 0, 7, 20, chess_move::NULL_MOVE, NodeType::Exact);
+    let entry = table.probe(&board, 0).unwrap();
+    assert_eq!(entry.depth, 7);
+    assert!(entry.best_move == m);
+
+    //A bound does not replace an exact score of the same search
+    table.store(&board, 0, 9, 30, m, NodeType::UpperBound);
+    assert_eq!(table.probe(&board, 0).unwrap().node_type, NodeType::Exact);
+}
+
+#[test]
+fn deeper_bounds_replace_bounds() {
+    let table = TranspositionTable::new(1);
+    let board = BitBoard::start_position();
+
+    table.store(&board, 0, 1, -40, chess_move::NULL_MOVE, NodeType::UpperBound);
+    table.store(&board, 0, 10, 25, chess_move::NULL_MOVE, NodeType::LowerBound);
+
+    let entry = table.probe(&board, 0).unwrap();
+    assert_eq!(entry.depth, 10);
+    assert_eq!(entry.score, 25);
+    assert_eq!(entry.node_type, NodeType::LowerBound);
+
+    //But a shallower bound of the other type does not push it out
+    table.store(&board, 0, 4, -10, chess_move::NULL_MOVE, NodeType::UpperBound);
+    assert_eq!(table.probe(&board, 0).unwrap().node_type, NodeType::LowerBound);
+
+    //Same depth, the newer bound wins
+    table.store(&board, 0, 10, 15, chess_move::NULL_MOVE, NodeType::UpperBound);
+    let entry = table.probe(&board, 0).unwrap();
+    assert_eq!(entry.node_type, NodeType::UpperBound);
+    assert_eq!(entry.score, 15);
+}
+
+#[test]
+fn old_entries_are_replaced() {
+    let table = TranspositionTable::new(1);
+    let board = BitBoard::start_position();
+
+    table.store(&board, 0, 10, 0, chess_move::NULL_MOVE, NodeType::Exact);
+    table.new_search();
+
+    table.store(&board, 0, 1, 5, chess_move::NULL_MOVE, NodeType::UpperBound);
+    let entry = table.probe(&board, 0).unwrap();
+    assert_eq!(entry.depth, 1);
+    assert_eq!(entry.node_type, NodeType::UpperBound);
+}
+
+#[test]
+fn hashfull_and_clear() {
+    let table = TranspositionTable::new(1);
+    assert_eq!(table.get_size_mb(), 1);
+    assert_eq!(table.get_hashfull(), 0);
+
+    let mut board = BitBoard::start_position();
+    let mut positions = vec![board];
+
+    //Fill the table with every position a few plies deep
+    for _ in 0..3 {
+        let mut next = Vec::new();
+        for position in &positions {
+            for m in position.get_legal_moves() {
+                board = position.clone();
+                board.make_move(m);
+                next.push(board);
+            }
+        }
+        positions = next;
+    }
+
+    for position in &positions {
+        table.store(position, 0, 1, 0, chess_move::NULL_MOVE, NodeType::Exact);
+    }
+
+    assert!(table.get_hashfull() > 0);
+
+    table.new_search();
+    assert_eq!(table.get_hashfull(), 0);
+
+    table.clear();
+    assert!(positions.iter().all(|p| table.probe(p, 0).is_none()));
+}
@@ -0,0 +1,74 @@
+use std::{io::Write, process::{Command, Stdio}};
+
+use barschbot::{chess_move, uci};
+
+//Pipes a scripted session into the engine binary and returns everything it printed
+fn run_session(commands: &[&str]) -> Vec<String> {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_barschbot"))
+        .arg("uci")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = engine.stdin.take().unwrap();
+    for command in commands {
+        writeln!(stdin, "{}", command).unwrap();
+    }
+    drop(stdin);
+
+    let output = engine.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    return String::from_utf8(output.stdout).unwrap().lines().map(|l| l.to_owned()).collect();
+}
+
+fn assert_legal_bestmove(lines: &[String], position: &[&str]) {
+    let bestmove = lines.iter().find(|l| l.starts_with("bestmove")).expect("no bestmove");
+    let parts = bestmove.split_whitespace().collect::<Vec<_>>();
+
+    let mut game = uci::parse_position(position).unwrap();
+    let m = game.get_uci_move(parts[1].to_owned());
+    assert!(m != chess_move::NULL_MOVE, "{}", bestmove);
+
+    //The ponder move has to be a legal reply
+    if parts.len() == 4 {
+        assert_eq!(parts[2], "ponder");
+        game.make_move(m);
+        assert!(game.get_uci_move(parts[3].to_owned()) != chess_move::NULL_MOVE, "{}", bestmove);
+    }
+}
+
+#[test]
+fn handshake_and_search() {
+    let lines = run_session(&["uci", "setoption name Ponder value true", "setoption name Hash value 8", "setoption name OwnBook value false",
+        "isready", "position startpos moves e2e4", "go depth 4"]);
+
+    assert!(lines.iter().any(|l| l == "uciok"));
+    assert!(lines.iter().any(|l| l == "option name Ponder type check default false"));
+    assert!(!lines.iter().any(|l| l.contains("unknown option")));
+    assert!(lines.iter().any(|l| l == "readyok"));
+    assert!(lines.iter().any(|l| l.starts_with("info depth 4")));
+    assert_legal_bestmove(&lines, &["startpos", "moves", "e2e4"]);
+}
+
+#[test]
+fn stop_and_ponderhit() {
+    let lines = run_session(&["position startpos", "go infinite", "isready", "stop", "quit"]);
+    assert!(lines.iter().any(|l| l == "readyok"));
+    assert_legal_bestmove(&lines, &["startpos"]);
+
+    let lines = run_session(&["position startpos moves e2e4 e7e5", "go ponder movetime 100", "ponderhit"]);
+    assert_eq!(lines.iter().filter(|l| l.starts_with("bestmove")).count(), 1);
+    assert_legal_bestmove(&lines, &["startpos", "moves", "e2e4", "e7e5"]);
+}
+
+#[test]
+fn quit_waits_for_queued_commands() {
+    let lines = run_session(&["position startpos", "go depth 6", "position startpos moves e2e4", "go depth 6", "quit"]);
+
+    let bestmoves = lines.iter().filter(|l| l.starts_with("bestmove")).cloned().collect::<Vec<_>>();
+    assert_eq!(bestmoves.len(), 2);
+    assert_legal_bestmove(&bestmoves[..1], &["startpos"]);
+    assert_legal_bestmove(&bestmoves[1..], &["startpos", "moves", "e2e4"]);
+}